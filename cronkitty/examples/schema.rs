@@ -1,11 +1,12 @@
 use cosmwasm_schema::write_api;
 
-use cronkitty::contract::{ContractExecMsg, ContractQueryMsg, InstantiateMsg};
+use cronkitty::contract::{ContractExecMsg, ContractQueryMsg, InstantiateMsg, MigrateMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         execute: ContractExecMsg,
         query: ContractQueryMsg,
+        migrate: MigrateMsg,
     }
 }
@@ -1,43 +1,320 @@
 use crate::error::ContractError;
+use crate::fees::{collapse_gas_limit, min_deposit, native_cost_per_execution, task_gas};
+use crate::migrations::{parse_version, STATE_TRANSFORMS};
+use crate::reply_id::ReplyOp;
+use crate::schedule::next_executions;
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    to_binary, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, Empty, Env, Event, MessageInfo,
-    Response, StdResult, SubMsg, WasmMsg,
+    from_binary, to_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut,
+    DistributionMsg, Empty, Env, Event, MessageInfo, Order, Response, StakingMsg, StdError,
+    StdResult, Storage, SubMsg, Timestamp, Uint128, WasmMsg,
 };
 use croncat_sdk_factory::state::CONTRACT_ADDRS;
 use croncat_sdk_manager::{
-    msg::ManagerExecuteMsg as CCManagerExecMsg, types::Config as CCManagerConfig,
+    msg::{
+        ManagerExecuteMsg as CCManagerExecMsg, ManagerQueryMsg as CCManagerQueryMsg,
+        ManagerReceiveMsg as CCManagerReceiveMsg,
+    },
+    types::{Config as CCManagerConfig, TaskBalance, TaskBalanceResponse},
 };
 use croncat_sdk_tasks::{
-    msg::TasksExecuteMsg as CCTaskExecMsg,
-    types::{Action, TaskExecutionInfo, TaskRequest},
+    msg::{TasksExecuteMsg as CCTaskExecMsg, TasksQueryMsg as CCTaskQueryMsg},
+    types::{
+        Action, Boundary, Config as CCTasksConfig, CosmosQuery, Interval, TaskExecutionInfo,
+        TaskInfo, TaskRequest, TaskResponse, Transform,
+    },
 };
-use cw2::set_contract_version;
-use cw_storage_plus::{Item, Map};
+use cw2::{get_contract_version, set_contract_version, ContractVersion};
+use cw20::{Cw20Coin, Cw20CoinVerified, Cw20ExecuteMsg};
+use cw_storage_plus::{Bound, Deque, Item, Map};
+use cw_utils::nonpayable;
+use std::collections::{BTreeMap, BTreeSet};
 use sylvia::contract;
 use vectis_wallet::ProxyExecuteMsg;
 
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-const TASK: &str = "tasks";
-const MANAGER: &str = "manager";
+pub(crate) const TASK: &str = "tasks";
+pub(crate) const MANAGER: &str = "manager";
 
+/// Croncat contract addrs by croncat factory, version and contract name
+type CroncatAddrs = BTreeMap<(Vec<u8>, [u8; 2], &'static str), Addr>;
+
+const DEFAULT_LIMIT: u32 = 10;
+/// Executions kept in the history of each action
+const HISTORY_SIZE: u64 = 20;
+const MAX_LIMIT: u32 = 30;
+
+/// The 0.2.x layout of an action:
 /// task / mgr version on croncat, msgs to execute, task_hash
 pub type CronkittyActionRef = ([u8; 2], Vec<CosmosMsg>, Option<String>);
 
+/// The 0.2.x `actions` map, only used to migrate to `CronKittyAction`
+pub(crate) const LEGACY_ACTIONS: Map<u64, CronkittyActionRef> = Map::new("actions");
+
+#[cw_serde]
+pub enum ActionStatus {
+    /// Task creation is sent to croncat, waiting for the task hash in the reply
+    Pending,
+    /// Task is on croncat and can be executed
+    Active,
+    /// Task stays on croncat but executions are skipped
+    Paused,
+    /// Task was removed by croncat, e.g. balance exhausted or boundary reached
+    Ended,
+    /// The failure limit was reached with `Remove`, executions are rejected
+    /// until `sync_actions` removes the task
+    Failed,
+}
+
+#[cw_serde]
+pub struct CronKittyAction {
+    /// task / mgr version on croncat
+    pub version: [u8; 2],
+    /// croncat factory the version is resolved on
+    pub croncat_factory: CanonicalAddr,
+    /// msgs for the proxy to execute
+    pub msgs: Vec<CosmosMsg>,
+    /// task_hash on croncat, set in the create task reply
+    pub task_hash: Option<String>,
+    /// block time of creation, `None` for actions migrated from 0.2.x
+    pub created_at: Option<Timestamp>,
+    pub status: ActionStatus,
+    pub label: Option<String>,
+    /// The task parameters to recreate the task on croncat
+    pub schedule: Option<CronKittySchedule>,
+    pub failure_limit: Option<CronKittyFailureLimit>,
+    /// Failures of the proxy call since the last success
+    pub consecutive_failures: u32,
+    pub spend_limit: Option<CronKittySpendLimit>,
+    /// Native coins sent by the msgs over all successful executions
+    pub spent: Vec<Coin>,
+}
+
+/// Caps on the native coins the msgs of an action send via bank sends / burns,
+/// wasm execute / instantiate funds and staking delegations.
+/// Denoms not in a cap cannot be sent. Msgs whose spend cannot be determined,
+/// e.g. custom, stargate or ibc msgs, are rejected while a cap is set.
+#[cw_serde]
+pub struct CronKittySpendLimit {
+    /// Maximum sent in one execution
+    pub per_execution: Option<Vec<Coin>>,
+    /// Maximum sent over all executions of the action
+    pub total: Option<Vec<Coin>>,
+}
+
+#[cw_serde]
+pub enum CronKittyOnFailureLimit {
+    Pause,
+    /// Marks the action as `Failed` as the task is not removed within its own croncat execution.
+    /// Croncat removes the task on the next execution if it has `stop_on_fail`, otherwise it
+    /// keeps charging the task until `sync_actions` is called, which is required to remove the
+    /// task or action and send the refund to the owner (proxy).
+    Remove,
+}
+
+#[cw_serde]
+pub struct CronKittyFailureLimit {
+    pub max_consecutive_failures: u32,
+    pub on_limit: CronKittyOnFailureLimit,
+}
+
+/// The parameters of a croncat task other than its action, which calls this contract
+#[cw_serde]
+pub struct CronKittySchedule {
+    pub interval: Interval,
+    pub boundary: Option<Boundary>,
+    pub stop_on_fail: bool,
+    pub queries: Option<Vec<CosmosQuery>>,
+    pub transforms: Option<Vec<Transform>>,
+    /// gas limit of the action calling this contract, i.e. the sum of the msgs gas limit
+    pub gas_limit: Option<u64>,
+}
+
+impl CronKittySchedule {
+    pub fn from_task(task: &TaskRequest) -> Result<Self, ContractError> {
+        Ok(Self {
+            interval: task.interval.clone(),
+            boundary: task.boundary.clone(),
+            stop_on_fail: task.stop_on_fail,
+            queries: task.queries.clone(),
+            transforms: task.transforms.clone(),
+            gas_limit: collapse_gas_limit(&task.actions)?,
+        })
+    }
+
+    /// The schedule of a task created by this contract on croncat
+    pub fn from_task_info(task: TaskInfo) -> Result<Self, ContractError> {
+        Ok(Self {
+            interval: task.interval,
+            boundary: Some(task.boundary),
+            stop_on_fail: task.stop_on_fail,
+            queries: task.queries,
+            transforms: Some(task.transforms),
+            gas_limit: collapse_gas_limit(&task.actions)?,
+        })
+    }
+
+    /// The task for croncat to call `action`
+    pub fn to_task(&self, action: Action, cw20: Option<Cw20Coin>) -> TaskRequest {
+        TaskRequest {
+            interval: self.interval.clone(),
+            boundary: self.boundary.clone(),
+            stop_on_fail: self.stop_on_fail,
+            actions: vec![action],
+            queries: self.queries.clone(),
+            transforms: self.transforms.clone(),
+            cw20,
+        }
+    }
+}
+
 #[cw_serde]
 pub struct CronKittyActionResp {
     pub msgs: Vec<CosmosMsg>,
     pub task_hash: Option<String>,
     pub task_addr: Addr,
     pub manager_addr: Addr,
+    pub created_at: Option<Timestamp>,
+    pub status: ActionStatus,
+    pub label: Option<String>,
+    /// `None` if the task was not found on croncat when migrating from 0.2.x
+    pub schedule: Option<CronKittySchedule>,
+    pub failure_limit: Option<CronKittyFailureLimit>,
+    pub consecutive_failures: u32,
+    pub spend_limit: Option<CronKittySpendLimit>,
+    pub spent: Vec<Coin>,
+}
+
+impl CronKittyActionResp {
+    pub fn new(action: CronKittyAction, task_addr: Addr, manager_addr: Addr) -> Self {
+        Self {
+            msgs: action.msgs,
+            task_hash: action.task_hash,
+            task_addr,
+            manager_addr,
+            created_at: action.created_at,
+            status: action.status,
+            label: action.label,
+            schedule: action.schedule,
+            failure_limit: action.failure_limit,
+            consecutive_failures: action.consecutive_failures,
+            spend_limit: action.spend_limit,
+            spent: action.spent,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct CronKittyTaskBalanceResp {
+    pub balance: Option<TaskBalance>,
+    /// Executions the native balance covers at the manager's current gas price and fees
+    pub executions_remaining: Option<u64>,
+}
+
+#[cw_serde]
+pub struct CronKittyTaskCostResp {
+    /// Gas croncat accounts for one execution, including croncat's base, action and query fees
+    pub gas: u64,
+    pub cost_per_execution: Coin,
+    /// The minimum native deposit for croncat to create the task
+    pub min_deposit: Coin,
+    /// The cost of the requested number of executions
+    pub cost_for_executions: Option<Coin>,
+}
+
+#[cw_serde]
+pub struct CronKittyConfigResp {
+    /// The Vectis account (proxy) this plugin executes for
+    pub owner: Addr,
+    pub croncat_factory: Addr,
+    pub next_action_id: u64,
+    pub active_actions: u64,
+    pub paused_actions: u64,
+    pub contract_version: ContractVersion,
+    /// Latest croncat tasks version on the factory, new tasks are created on it
+    pub croncat_tasks_version: Option<[u8; 2]>,
+    /// Latest croncat manager version on the factory
+    pub croncat_manager_version: Option<[u8; 2]>,
+    pub terminated: bool,
+}
+
+#[cw_serde]
+pub enum CronKittyExecutionOutcome {
+    Success,
+    /// The proxy failed to execute the msgs
+    Failure {
+        error: String,
+    },
+}
+
+#[cw_serde]
+pub struct CronKittyExecutionRecord {
+    pub height: u64,
+    pub time: Timestamp,
+    /// croncat's info of the execution calling this contract
+    pub task_execution_info: TaskExecutionInfo,
+    /// Set in the reply of the proxy call, `None` until then
+    pub outcome: Option<CronKittyExecutionOutcome>,
+}
+
+#[cw_serde]
+pub struct CronKittyExecutionHistoryResp {
+    /// Total executions of the action
+    pub executions: u64,
+    /// The latest executions, newest first
+    pub history: Vec<CronKittyExecutionRecord>,
+}
+
+/// Caps on the native coins sent by all actions within a window,
+/// counted the same way as `CronKittySpendLimit`. Denoms not in the limit cannot be sent.
+#[cw_serde]
+pub struct CronKittyBudget {
+    pub limit: Vec<Coin>,
+    /// Length of the rolling window in seconds,
+    /// what was sent in the last `window` seconds counts against the limit
+    pub window: u64,
+}
+
+/// What the msgs of an execution sent, counted against the budget until it leaves the window
+#[cw_serde]
+pub struct CronKittyBudgetSpend {
+    pub time: Timestamp,
+    pub spent: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct CronKittyBudgetResp {
+    pub budget: Option<CronKittyBudget>,
+    /// Sent within the window up to the current block
+    pub spent: Vec<Coin>,
+    /// What can still be sent within the window
+    pub remaining: Vec<Coin>,
+}
+
+/// A predicted execution of a task, by block height or by block time for `Interval::Cron`
+#[cw_serde]
+pub enum CronKittyExecutionSlot {
+    Block(u64),
+    Time(Timestamp),
+}
+
+/// Messages sent by the proxy along with cw20 tokens via `Cw20ExecuteMsg::Send`
+#[cw_serde]
+pub enum CronKittyReceiveMsg {
+    /// Deposits the tokens on the croncat manager for `CreateTask` with `task.cw20` set,
+    /// the proxy sends this in the same transaction as `CreateTask`
+    Deposit {},
+    /// Adds the tokens to the cw20 balance of the action's task on croncat
+    RefillTaskCw20 { action_id: u64 },
 }
 
 pub struct CronKittyPlugin<'a> {
-    // Pending get task hash
-    // Map <action_id, (task_version, mg_version, msg_for_proxy_to_exec, task_hash_on_croncat )>
-    pub actions: Map<'a, u64, CronkittyActionRef>,
+    pub actions: Map<'a, u64, CronKittyAction>,
+    // Reverse index of actions
+    // Map <task_hash_on_croncat, action_id>
+    pub task_hashes: Map<'a, &'a str, u64>,
     pub owner: Item<'a, CanonicalAddr>,
     pub next_action_id: Item<'a, u64>,
     pub croncat_factory: Item<'a, CanonicalAddr>,
@@ -46,6 +323,23 @@ pub struct CronKittyPlugin<'a> {
     pub latest_versions: Map<'a, &'a str, [u8; 2]>,
     pub last_task_execution_info: Item<'a, TaskExecutionInfo>,
     pub manager_config: Item<'a, CCManagerConfig>,
+    pub tasks_config: Item<'a, CCTasksConfig>,
+    // cw20 deposited on the croncat manager for tasks not yet created
+    // Map <(croncat manager addr, cw20 addr), amount>
+    pub cw20_deposits: Map<'a, (&'a Addr, &'a Addr), Uint128>,
+    // Set by `teardown`, no tasks can be created afterwards
+    pub terminated: Item<'a, bool>,
+    // Map <action_id, number of executions>
+    pub execution_counts: Map<'a, u64, u64>,
+    // Ring buffer of the latest `HISTORY_SIZE` executions of each action
+    // Map <(action_id, execution number % HISTORY_SIZE), record>
+    pub execution_history: Map<'a, (u64, u64), CronKittyExecutionRecord>,
+    pub budget: Item<'a, CronKittyBudget>,
+    // Spends of the executions within the budget window, oldest first
+    pub budget_spends: Deque<'a, CronKittyBudgetSpend>,
+    // Number of actions in each status, kept in line by `save_action` / `remove_action`
+    pub active_actions: Item<'a, u64>,
+    pub paused_actions: Item<'a, u64>,
 }
 
 #[contract]
@@ -53,12 +347,22 @@ impl CronKittyPlugin<'_> {
     pub const fn new() -> Self {
         Self {
             actions: Map::new("actions"),
+            task_hashes: Map::new("task_hashes"),
             owner: Item::new("owner"),
             next_action_id: Item::new("id"),
             croncat_factory: Item::new("croncat-manager"),
             latest_versions: Map::new("latest_versions"),
             last_task_execution_info: Item::new("last_task_execution_info"),
             manager_config: Item::new("config"),
+            tasks_config: Item::new("config"),
+            cw20_deposits: Map::new("cw20_deposits"),
+            terminated: Item::new("terminated"),
+            execution_counts: Map::new("execution_counts"),
+            execution_history: Map::new("execution_history"),
+            budget: Item::new("budget"),
+            budget_spends: Deque::new("budget_spends"),
+            active_actions: Item::new("active_actions"),
+            paused_actions: Item::new("paused_actions"),
         }
     }
 
@@ -93,9 +397,14 @@ impl CronKittyPlugin<'_> {
         ctx: (DepsMut, Env, MessageInfo),
         action_id: u64,
     ) -> Result<Response, ContractError> {
-        let (deps, _, info) = ctx;
-        let (version, msgs, task_hash_stored) = self.actions.load(deps.storage, action_id)?;
-        let mgt_addr = self.query_contract_addr(&deps.as_ref(), &version, MANAGER)?;
+        let (deps, env, info) = ctx;
+        let action = self.actions.load(deps.storage, action_id)?;
+        let mgt_addr = self.query_contract_addr(
+            &deps.as_ref(),
+            &action.croncat_factory,
+            &action.version,
+            MANAGER,
+        )?;
 
         // Make sure it is from the maanger
         if info.sender != mgt_addr {
@@ -108,22 +417,53 @@ impl CronKittyPlugin<'_> {
             .last_task_execution_info
             .query(&deps.querier, mgt_addr)?;
 
-        if let Some(task_hash) = task_hash_stored {
-            if task_info.task_hash != task_hash {
+        if let Some(task_hash) = &action.task_hash {
+            if &task_info.task_hash != task_hash {
                 Err(ContractError::UnexpectedCroncatTaskHash)
+            } else if action.status == ActionStatus::Paused {
+                // Succeed so that the agent is not penalised and the task stays on croncat
+                Ok(Response::new().add_event(
+                    Event::new("vectis.cronkitty.v1.MsgExecuteSkipped")
+                        .add_attribute("Task ID", action_id.to_string())
+                        .add_attribute("Reason", "paused"),
+                ))
+            } else if action.status == ActionStatus::Failed {
+                // Fail so that croncat removes a task with `stop_on_fail`
+                Err(ContractError::ActionFailed)
             } else {
+                let budget = self.budget.may_load(deps.storage)?;
+                if action.spend_limit.is_some() || budget.is_some() {
+                    let spend = msgs_spend(&action.msgs)?.ok_or(ContractError::UnmeteredSpend)?;
+                    if let Some(limit) = &action.spend_limit {
+                        check_spend_limit(limit, &spend, &action.spent)?;
+                    }
+                    if let Some(budget) = budget {
+                        self.prune_budget_spends(deps.storage, &env, &budget)?;
+                        let spent = self.budget_spent(deps.storage, &env, &budget)?;
+                        check_budget(&budget, &spend, &spent)?;
+                    }
+                }
+
                 let owner = deps
                     .api
                     .addr_humanize(&self.owner.load(deps.storage)?)?
                     .into_string();
-                let msg = CosmosMsg::<_>::Wasm(WasmMsg::Execute {
-                    contract_addr: owner.clone(),
-                    msg: to_binary(&ProxyExecuteMsg::PluginExecute { msgs })?,
-                    funds: vec![],
-                });
-                let event =
-                    Event::new("vectis.cronkitty.v1.MsgExecute").add_attribute("Proxy", owner);
-                Ok(Response::new().add_event(event).add_message(msg))
+                // The outcome is recorded in the reply, a failure of the proxy does not fail the
+                // croncat execution so that the agent is paid
+                let msg = SubMsg::reply_always(
+                    CosmosMsg::<_>::Wasm(WasmMsg::Execute {
+                        contract_addr: owner.clone(),
+                        msg: to_binary(&ProxyExecuteMsg::PluginExecute { msgs: action.msgs })?,
+                        funds: vec![],
+                    }),
+                    ReplyOp::ProxyExecute.reply_id(action_id)?,
+                );
+                let executions = self.record_execution(deps, &env, action_id, task_info)?;
+                let event = Event::new("vectis.cronkitty.v1.MsgExecute")
+                    .add_attribute("Proxy", owner)
+                    .add_attribute("Task ID", action_id.to_string())
+                    .add_attribute("Executions", executions.to_string());
+                Ok(Response::new().add_event(event).add_submessage(msg))
             }
         } else {
             Err(ContractError::TaskHashNotFound)
@@ -134,7 +474,8 @@ impl CronKittyPlugin<'_> {
     fn create_task(
         &self,
         ctx: (DepsMut, Env, MessageInfo),
-        mut task: TaskRequest,
+        task: TaskRequest,
+        label: Option<String>,
     ) -> Result<Response, ContractError> {
         let (deps, env, info) = ctx;
 
@@ -142,84 +483,37 @@ impl CronKittyPlugin<'_> {
         if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
             Err(ContractError::Unauthorized)
         } else {
-            // guarenteed by croncat that TASK and MANAGER are the same version
-            let contract_version =
-                self.query_latest_version_croncat_contract(&deps.as_ref(), TASK)?;
-            let task_contract_addr =
-                self.query_contract_addr(&deps.as_ref(), &contract_version, TASK)?;
-
-            // The id for croncat to call back
-            let id = self.next_action_id.load(deps.storage)?;
-            self.actions.save(
-                deps.storage,
-                id,
-                &(
-                    contract_version,
-                    task.actions.iter().cloned().map(|a| a.msg).collect(),
-                    None,
-                ),
-            )?;
-
-            // This sums up all the action gas into one because croncat manager will only know the
-            // action id on this contract to call.
-            // Each task's gas_limit is provided by simulation in the frontend on croncat
-            // TODO: We can do something similar to their contracts `validate_msg_calculate_usage` method
-            // as well.
-            //
-            // Croncat logic:
-            // Task contract calculates the total gas specified by user for each task,
-            // it then creates fund balance for the task on the manager (who holds the funds sent)
-            // On storing the task balance, the manager checks there is enough funds (gas_limit,
-            // fees for croncat, native, cw20, ibc, etc)
-            // The required fee per action is gas_base_fee + gas_action_fee + gas_limit +
-            // treasury_fee + agent_fee. If it is not a one-off task, the fees are multipled by 2.
-            //
-            // Since Vectis Accounts will be self-custody, croncat only need to check that the gas is
-            // enough. This is calculated in `execute_create_task_balance` on the manager
-            // We are not checking it here
-
-            let gas_limit = task.actions.iter().try_fold(0u64, |acc, a| {
-                acc.checked_add(a.gas_limit.unwrap_or(0))
-                    .ok_or(ContractError::Overflow)
-            })?;
-
-            let gas_limit = if gas_limit == 0 {
-                None
-            } else {
-                Some(gas_limit)
-            };
+            let msg = self.create_task_submsg(deps, &env, task, label, info.funds)?;
 
-            // This is the action stored on Croncat contract
-            let action = Action {
-                msg: CosmosMsg::<Empty>::Wasm(WasmMsg::Execute {
-                    contract_addr: env.contract.address.to_string(),
-                    msg: to_binary(&ExecMsg::Execute { action_id: id })?,
-                    funds: vec![],
-                }),
-                gas_limit,
-            };
+            Ok(Response::new().add_submessage(msg))
+        }
+    }
 
-            // We forward all the other params (so we can contribute to / use to frontend code from
-            // croncat)
-            // The Action called is to call this plugin at the given intervals
-            task.actions = vec![action];
-            task.cw20 = None;
+    /// Creates the tasks with consecutive action ids,
+    /// the funds sent must be the sum of the funds of each task
+    #[msg(exec)]
+    fn create_tasks(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+        tasks: Vec<(TaskRequest, Vec<Coin>)>,
+    ) -> Result<Response, ContractError> {
+        let (mut deps, env, info) = ctx;
 
-            let msg = SubMsg::reply_on_success(
-                CosmosMsg::Wasm(WasmMsg::Execute {
-                    contract_addr: task_contract_addr.to_string(),
-                    msg: to_binary(&CCTaskExecMsg::CreateTask {
-                        task: Box::new(task),
-                    })?,
-                    // TODO: This is the value the user provides for the task execution.
-                    // https://github.com/CronCats/cw-croncat/issues/204
-                    funds: info.funds,
-                }),
-                id,
-            );
+        // only the owner (proxy) can create task
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
 
-            Ok(Response::new().add_submessage(msg))
+        if sum_coins(info.funds.iter())? != sum_coins(tasks.iter().flat_map(|(_, f)| f.iter()))? {
+            return Err(ContractError::FundsMismatch);
         }
+
+        let msgs = tasks
+            .into_iter()
+            .map(|(task, funds)| self.create_task_submsg(deps.branch(), &env, task, None, funds))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Response::new().add_submessages(msgs))
     }
 
     #[msg(exec)]
@@ -233,124 +527,1941 @@ impl CronKittyPlugin<'_> {
         // only the owner (proxy) can create task
         if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
             Err(ContractError::Unauthorized)
-        } else if let (contract_version, _, Some(task_hash)) =
-            self.actions.load(deps.storage, task_id)?
-        {
-            let task = self.query_contract_addr(&deps.as_ref(), &contract_version, TASK)?;
-            let msg = SubMsg::reply_on_success(
+        } else {
+            let msg = self.remove_task_or_ended_action(deps, task_id)?;
+            Ok(Response::new().add_submessages(msg))
+        }
+    }
+
+    #[msg(exec)]
+    pub fn remove_tasks(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+        action_ids: Vec<u64>,
+    ) -> Result<Response, ContractError> {
+        let (mut deps, _env, info) = ctx;
+
+        // only the owner (proxy) can remove task
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let mut msgs = vec![];
+        for id in action_ids {
+            msgs.extend(self.remove_task_or_ended_action(deps.branch(), id)?);
+        }
+
+        Ok(Response::new().add_submessages(msgs))
+    }
+
+    /// Updates the msgs and / or the schedule of an action.
+    /// Croncat only knows about `ExecMsg::Execute { action_id }`, so msg-only changes are local.
+    /// Schedule changes recreate the task on croncat under the same `action_id`,
+    /// moving the remaining task balance across.
+    #[msg(exec)]
+    pub fn update_task(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+        action_id: u64,
+        msgs: Option<Vec<CosmosMsg>>,
+        interval: Option<Interval>,
+        boundary: Option<Boundary>,
+    ) -> Result<Response, ContractError> {
+        let (deps, _env, info) = ctx;
+
+        // only the owner (proxy) can update task
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let mut action = self.actions.load(deps.storage, action_id)?;
+        let task_hash = action
+            .task_hash
+            .clone()
+            .ok_or(ContractError::TaskHashNotFound)?;
+        if let Some(msgs) = msgs {
+            // Under a cap every execution of unmetered msgs would be rejected
+            if (action.spend_limit.is_some() || self.budget.may_load(deps.storage)?.is_some())
+                && msgs_spend(&msgs)?.is_none()
+            {
+                return Err(ContractError::UnmeteredSpend);
+            }
+            action.msgs = msgs;
+        }
+
+        let mut res = Response::new();
+        if interval.is_none() && boundary.is_none() {
+            // Funds are only used to recreate the task
+            nonpayable(&info)?;
+        } else {
+            let task_addr = self.query_contract_addr(
+                &deps.as_ref(),
+                &action.croncat_factory,
+                &action.version,
+                TASK,
+            )?;
+            let manager_addr = self.query_contract_addr(
+                &deps.as_ref(),
+                &action.croncat_factory,
+                &action.version,
+                MANAGER,
+            )?;
+
+            let croncat_task = deps
+                .querier
+                .query_wasm_smart::<TaskResponse>(
+                    &task_addr,
+                    &CCTaskQueryMsg::Task {
+                        task_hash: task_hash.clone(),
+                    },
+                )?
+                .task
+                .ok_or(ContractError::TaskNotFound)?;
+
+            let (funds, cw20) =
+                self.task_refund(&deps.as_ref(), &manager_addr, &task_hash, info.funds)?;
+            // cw20 is refunded to the temp balance of this contract on the manager
+            let cw20 = cw20.map(|c| Cw20Coin {
+                address: c.address.to_string(),
+                amount: c.amount,
+            });
+
+            // The action calling this contract is kept as is
+            let task = TaskRequest {
+                interval: interval.unwrap_or(croncat_task.interval),
+                boundary: boundary.or(Some(croncat_task.boundary)),
+                stop_on_fail: croncat_task.stop_on_fail,
+                actions: croncat_task.actions,
+                queries: croncat_task.queries,
+                transforms: Some(croncat_task.transforms),
+                cw20,
+            };
+            let schedule = CronKittySchedule::from_task(&task)?;
+
+            self.task_hashes.remove(deps.storage, &task_hash);
+            let remove_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: task_addr.to_string(),
+                msg: to_binary(&CCTaskExecMsg::RemoveTask { task_hash })?,
+                funds: vec![],
+            });
+            let create_msg = SubMsg::reply_on_success(
                 CosmosMsg::Wasm(WasmMsg::Execute {
-                    contract_addr: task.to_string(),
-                    msg: to_binary(&CCTaskExecMsg::RemoveTask { task_hash })?,
-                    funds: vec![],
+                    contract_addr: task_addr.to_string(),
+                    msg: to_binary(&CCTaskExecMsg::CreateTask {
+                        task: Box::new(task),
+                    })?,
+                    funds,
                 }),
-                task_id,
+                ReplyOp::CreateTask.reply_id(action_id)?,
             );
-            Ok(Response::new().add_submessage(msg))
-        } else {
-            Err(ContractError::TaskHashNotFound)
+
+            // The new task hash is set in the reply, paused actions stay paused
+            action.schedule = Some(schedule);
+            action.task_hash = None;
+            if action.status == ActionStatus::Active {
+                action.status = ActionStatus::Pending;
+            }
+            res = res.add_message(remove_msg).add_submessage(create_msg);
         }
+
+        self.save_action(deps.storage, action_id, &action)?;
+
+        Ok(res.add_event(
+            Event::new("vectis.cronkitty.v1.MsgUpdateTask")
+                .add_attribute("Task ID", action_id.to_string()),
+        ))
     }
 
+    /// Recreates the task of the action on the latest croncat version of the current factory,
+    /// keeping the action id. The refunded task balance, along with any funds sent,
+    /// is used for the new task.
     #[msg(exec)]
-    pub fn refill_task(
+    pub fn migrate_action_to_latest(
         &self,
         ctx: (DepsMut, Env, MessageInfo),
-        task_id: u64,
+        action_id: u64,
     ) -> Result<Response, ContractError> {
-        let (deps, _env, info) = ctx;
+        let (deps, env, info) = ctx;
 
-        if info.funds.is_empty() {
-            return Err(ContractError::EmptyFunds);
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
         }
 
-        // only the owner (proxy) can create task
+        let (remove_msg, create_msg, event) = self
+            .migrate_action_msgs(deps, &env, action_id, info.funds)?
+            .ok_or(ContractError::ActionOnLatestVersion)?;
+
+        Ok(Response::new()
+            .add_message(remove_msg)
+            .add_submessage(create_msg)
+            .add_event(event))
+    }
+
+    /// Recreates the tasks of the actions on the latest croncat version of the current factory,
+    /// actions already on the latest version are skipped.
+    /// The new tasks are funded by the refunded task balances only.
+    #[msg(exec)]
+    pub fn migrate_actions_to_latest(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+        action_ids: Vec<u64>,
+    ) -> Result<Response, ContractError> {
+        let (mut deps, env, info) = ctx;
+
         if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
-            Err(ContractError::Unauthorized)
-        } else {
-            // call croncat to refill task
-            if let (contract_version, _, Some(task_hash)) =
-                self.actions.load(deps.storage, task_id)?
+            return Err(ContractError::Unauthorized);
+        }
+        nonpayable(&info)?;
+
+        let mut res = Response::new();
+        for action_id in action_ids {
+            if let Some((remove_msg, create_msg, event)) =
+                self.migrate_action_msgs(deps.branch(), &env, action_id, vec![])?
             {
-                let manager =
-                    self.query_contract_addr(&deps.as_ref(), &contract_version, MANAGER)?;
-                let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-                    contract_addr: manager.to_string(),
-                    msg: to_binary(&CCManagerExecMsg::RefillTaskBalance { task_hash })?,
-                    funds: info.funds,
-                });
-                Ok(Response::new().add_message(msg))
-            } else {
-                Err(ContractError::TaskHashNotFound)
+                res = res
+                    .add_message(remove_msg)
+                    .add_submessage(create_msg)
+                    .add_event(event);
             }
         }
-    }
 
-    #[msg(query)]
-    pub fn action_id(&self, ctx: (Deps, Env)) -> StdResult<u64> {
-        let (deps, _) = ctx;
-        self.next_action_id.load(deps.storage)
+        Ok(res)
     }
 
-    // These are the id that stores the actual cosmos messages
-    #[msg(query)]
-    pub fn action(
+    /// Pauses an action, croncat executions are skipped but the task and balance remain
+    #[msg(exec)]
+    pub fn pause_action(
         &self,
-        ctx: (Deps, Env),
+        ctx: (DepsMut, Env, MessageInfo),
         action_id: u64,
-    ) -> Result<CronKittyActionResp, ContractError> {
-        let (deps, _) = ctx;
-        let (contract_version, msgs, task_hash) = self.actions.load(deps.storage, action_id)?;
-        let task_addr = self.query_contract_addr(&deps, &contract_version, TASK)?;
-        let manager_addr = self.query_contract_addr(&deps, &contract_version, MANAGER)?;
-        Ok(CronKittyActionResp {
-            msgs,
-            task_hash,
-            task_addr,
-            manager_addr,
-        })
-    }
+    ) -> Result<Response, ContractError> {
+        let (deps, _env, info) = ctx;
 
-    #[msg(migrate)]
-    fn migrate(&self, _ctx: (DepsMut, Env)) -> Result<Response, ContractError> {
-        // Not used but required for impl for multitest
-        Ok(Response::default())
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let mut action = self.actions.load(deps.storage, action_id)?;
+        if action.status != ActionStatus::Active {
+            return Err(ContractError::ActionNotActive);
+        }
+        action.status = ActionStatus::Paused;
+        self.save_action(deps.storage, action_id, &action)?;
+
+        Ok(Response::new().add_event(
+            Event::new("vectis.cronkitty.v1.MsgPauseAction")
+                .add_attribute("Task ID", action_id.to_string()),
+        ))
     }
 
-    fn query_latest_version_croncat_contract(
+    #[msg(exec)]
+    pub fn resume_action(
         &self,
-        deps: &Deps,
-        name: &str,
-    ) -> Result<[u8; 2], ContractError> {
-        let cc_factory = deps
-            .api
-            .addr_humanize(&self.croncat_factory.load(deps.storage)?)?;
+        ctx: (DepsMut, Env, MessageInfo),
+        action_id: u64,
+    ) -> Result<Response, ContractError> {
+        let (deps, _env, info) = ctx;
 
-        self.latest_versions
-            .query(&deps.querier, cc_factory, name)
-            .transpose()
-            .ok_or_else(|| ContractError::NoCronCatVersion {
-                name: name.to_string(),
-            })?
-            .map_err(|e| e.into())
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let mut action = self.actions.load(deps.storage, action_id)?;
+        if action.status != ActionStatus::Paused {
+            return Err(ContractError::ActionNotPaused);
+        }
+        // A paused action whose task is being recreated by `update_task` is set active in the
+        // create task reply
+        action.status = if action.task_hash.is_some() {
+            ActionStatus::Active
+        } else {
+            ActionStatus::Pending
+        };
+        action.consecutive_failures = 0;
+        self.save_action(deps.storage, action_id, &action)?;
+
+        Ok(Response::new().add_event(
+            Event::new("vectis.cronkitty.v1.MsgResumeAction")
+                .add_attribute("Task ID", action_id.to_string()),
+        ))
     }
 
-    /// Takes a CronCat contract name, queries the factory for the latest contract address.
-    /// Returns a result with the latest version and the addr, or an error.
-    fn query_contract_addr(
+    /// Sets what happens to the action after the proxy fails to execute its msgs
+    /// `max_consecutive_failures` times in a row, `None` to keep executing it
+    #[msg(exec)]
+    pub fn set_failure_limit(
         &self,
-        deps: &Deps,
-        version: &[u8; 2],
-        name: &str,
-    ) -> Result<Addr, ContractError> {
-        CONTRACT_ADDRS
-            .query(
-                &deps.querier,
-                deps.api
-                    .addr_humanize(&self.croncat_factory.load(deps.storage)?)?,
-                (name, version),
-            )?
-            .ok_or_else(|| ContractError::NoCronCatContract {
-                name: name.to_string(),
+        ctx: (DepsMut, Env, MessageInfo),
+        action_id: u64,
+        limit: Option<CronKittyFailureLimit>,
+    ) -> Result<Response, ContractError> {
+        let (deps, _env, info) = ctx;
+
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
+        if matches!(&limit, Some(l) if l.max_consecutive_failures == 0) {
+            return Err(ContractError::InvalidFailureLimit);
+        }
+
+        let mut action = self.actions.load(deps.storage, action_id)?;
+        let event = Event::new("vectis.cronkitty.v1.MsgSetFailureLimit")
+            .add_attribute("Task ID", action_id.to_string())
+            .add_attribute(
+                "Max Consecutive Failures",
+                limit.as_ref().map_or("none".to_string(), |l| {
+                    l.max_consecutive_failures.to_string()
+                }),
+            );
+        action.failure_limit = limit;
+        self.save_action(deps.storage, action_id, &action)?;
+
+        Ok(Response::new().add_event(event))
+    }
+
+    /// Sets the caps on the native coins the msgs of all actions can send within a window,
+    /// `None` to remove them.
+    /// Rejected while an action that has not ended has msgs whose spend cannot be determined.
+    #[msg(exec)]
+    pub fn set_budget(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+        budget: Option<CronKittyBudget>,
+    ) -> Result<Response, ContractError> {
+        let (deps, _env, info) = ctx;
+
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let mut event = Event::new("vectis.cronkitty.v1.MsgSetBudget");
+        match budget {
+            Some(budget) => {
+                if budget.window == 0 {
+                    return Err(ContractError::InvalidBudget);
+                }
+                // Every execution of unmetered msgs would be rejected under the budget
+                let mut action_ids = vec![];
+                for r in self
+                    .actions
+                    .range(deps.storage, None, None, Order::Ascending)
+                {
+                    let (id, action) = r?;
+                    if action.status != ActionStatus::Ended && msgs_spend(&action.msgs)?.is_none() {
+                        action_ids.push(id);
+                    }
+                }
+                if !action_ids.is_empty() {
+                    return Err(ContractError::UnmeteredActions { action_ids });
+                }
+                event = event.add_attribute("Window", budget.window.to_string());
+                self.budget.save(deps.storage, &budget)?;
+            }
+            None => {
+                self.budget.remove(deps.storage);
+                while self.budget_spends.pop_front(deps.storage)?.is_some() {}
+            }
+        }
+
+        Ok(Response::new().add_event(event))
+    }
+
+    /// Sets the caps on the native coins the msgs of the action can send,
+    /// `None` to remove them
+    #[msg(exec)]
+    pub fn set_spend_limit(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+        action_id: u64,
+        limit: Option<CronKittySpendLimit>,
+    ) -> Result<Response, ContractError> {
+        let (deps, _env, info) = ctx;
+
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let mut action = self.actions.load(deps.storage, action_id)?;
+        if limit.is_some() && msgs_spend(&action.msgs)?.is_none() {
+            return Err(ContractError::UnmeteredSpend);
+        }
+        action.spend_limit = limit;
+        self.save_action(deps.storage, action_id, &action)?;
+
+        Ok(Response::new().add_event(
+            Event::new("vectis.cronkitty.v1.MsgSetSpendLimit")
+                .add_attribute("Task ID", action_id.to_string()),
+        ))
+    }
+
+    #[msg(exec)]
+    pub fn refill_task(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+        task_id: u64,
+    ) -> Result<Response, ContractError> {
+        let (deps, _env, info) = ctx;
+
+        if info.funds.is_empty() {
+            return Err(ContractError::EmptyFunds);
+        }
+
+        // only the owner (proxy) can create task
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            Err(ContractError::Unauthorized)
+        } else {
+            // call croncat to refill task
+            if let CronKittyAction {
+                version,
+                croncat_factory,
+                task_hash: Some(task_hash),
+                ..
+            } = self.actions.load(deps.storage, task_id)?
+            {
+                let manager =
+                    self.query_contract_addr(&deps.as_ref(), &croncat_factory, &version, MANAGER)?;
+                let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: manager.to_string(),
+                    msg: to_binary(&CCManagerExecMsg::RefillTaskBalance { task_hash })?,
+                    funds: info.funds,
+                });
+                Ok(Response::new().add_message(msg))
+            } else {
+                Err(ContractError::TaskHashNotFound)
+            }
+        }
+    }
+
+    /// Cw20 receive hook, `sender` must be the owner (proxy)
+    #[msg(exec)]
+    pub fn receive(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+        sender: String,
+        amount: Uint128,
+        msg: Binary,
+    ) -> Result<Response, ContractError> {
+        let (deps, _env, info) = ctx;
+
+        if deps.api.addr_validate(&sender)?
+            != deps.api.addr_humanize(&self.owner.load(deps.storage)?)?
+        {
+            return Err(ContractError::Unauthorized);
+        }
+        if amount.is_zero() {
+            return Err(ContractError::EmptyFunds);
+        }
+
+        // The cw20 contract is the sender of the hook
+        let token = info.sender;
+        match from_binary::<CronKittyReceiveMsg>(&msg)? {
+            CronKittyReceiveMsg::Deposit {} => {
+                // No task can use the deposit
+                if self.terminated.may_load(deps.storage)?.unwrap_or(false) {
+                    return Err(ContractError::Terminated);
+                }
+                let croncat_factory = self.croncat_factory.load(deps.storage)?;
+                let version = self.query_latest_version_croncat_contract(
+                    &deps.as_ref(),
+                    &croncat_factory,
+                    MANAGER,
+                )?;
+                let manager =
+                    self.query_contract_addr(&deps.as_ref(), &croncat_factory, &version, MANAGER)?;
+                self.cw20_deposits.update(
+                    deps.storage,
+                    (&manager, &token),
+                    |deposit| -> StdResult<_> {
+                        Ok(deposit.unwrap_or_default().checked_add(amount)?)
+                    },
+                )?;
+
+                Ok(Response::new()
+                    .add_message(self.cw20_to_manager_msg(&token, &manager, amount)?)
+                    .add_event(
+                        Event::new("vectis.cronkitty.v1.MsgDepositCw20")
+                            .add_attribute("Token", token)
+                            .add_attribute("Amount", amount),
+                    ))
+            }
+            CronKittyReceiveMsg::RefillTaskCw20 { action_id } => {
+                let action = self.actions.load(deps.storage, action_id)?;
+                let task_hash = action.task_hash.ok_or(ContractError::TaskHashNotFound)?;
+                let manager = self.query_contract_addr(
+                    &deps.as_ref(),
+                    &action.croncat_factory,
+                    &action.version,
+                    MANAGER,
+                )?;
+
+                // The tokens are added to the temp balance of this contract on the manager
+                // before being moved to the task balance
+                let refill_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: manager.to_string(),
+                    msg: to_binary(&CCManagerExecMsg::RefillTaskCw20Balance {
+                        task_hash,
+                        cw20: Cw20Coin {
+                            address: token.to_string(),
+                            amount,
+                        },
+                    })?,
+                    funds: vec![],
+                });
+
+                Ok(Response::new()
+                    .add_message(self.cw20_to_manager_msg(&token, &manager, amount)?)
+                    .add_message(refill_msg))
+            }
+        }
+    }
+
+    /// Checks the croncat task of each action and marks the action as `Ended` if croncat has
+    /// already removed it. The tasks of `Failed` actions are removed on croncat.
+    /// Croncat refunds are then forwarded to the owner (proxy).
+    #[msg(exec)]
+    pub fn sync_actions(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Response, ContractError> {
+        let (mut deps, env, info) = ctx;
+
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let actions = self
+            .actions
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let mut croncat_addrs = CroncatAddrs::new();
+        // managers holding the cw20 refunds of ended tasks
+        let mut managers = BTreeSet::new();
+        let mut events = vec![];
+        let mut remove_msgs = vec![];
+        for (id, mut action) in actions {
+            let task_hash = match &action.task_hash {
+                Some(task_hash) => task_hash.clone(),
+                None => continue,
+            };
+            let task_addr =
+                self.cached_contract_addr(&deps.as_ref(), &mut croncat_addrs, &action, TASK)?;
+
+            let croncat_task = deps
+                .querier
+                .query_wasm_smart::<TaskResponse>(
+                    task_addr,
+                    &CCTaskQueryMsg::Task {
+                        task_hash: task_hash.clone(),
+                    },
+                )?
+                .task;
+
+            if croncat_task.is_none() {
+                managers.insert(self.cached_contract_addr(
+                    &deps.as_ref(),
+                    &mut croncat_addrs,
+                    &action,
+                    MANAGER,
+                )?);
+                self.task_hashes.remove(deps.storage, &task_hash);
+                action.task_hash = None;
+                action.status = ActionStatus::Ended;
+                self.save_action(deps.storage, id, &action)?;
+                events.push(
+                    Event::new("vectis.cronkitty.v1.SyncAction")
+                        .add_attribute("Task ID", id.to_string())
+                        .add_attribute("Task Hash", task_hash)
+                        .add_attribute("Status", "ended"),
+                );
+            } else if action.status == ActionStatus::Failed {
+                // The action is removed and the refund forwarded in the reply
+                remove_msgs.push(self.remove_task_submsg(deps.as_ref(), id)?);
+                events.push(
+                    Event::new("vectis.cronkitty.v1.SyncAction")
+                        .add_attribute("Task ID", id.to_string())
+                        .add_attribute("Task Hash", task_hash)
+                        .add_attribute("Status", "failed"),
+                );
+            }
+        }
+
+        let mut res = self.refund_owner(deps.as_ref(), &env, None)?;
+        for manager in managers {
+            res = self.withdraw_cw20(deps.branch(), &env, &manager, res)?;
+        }
+        Ok(res.add_submessages(remove_msgs).add_events(events))
+    }
+
+    /// Removes the tasks on croncat and puts the plugin in a terminal state rejecting new tasks,
+    /// to be called by the proxy before uninstalling the plugin.
+    /// Up to `limit` (at least 1) actions are removed per call, it is repeated until
+    /// the `Completed` attribute of the event is `true`.
+    /// Croncat refunds are forwarded to the owner (proxy) in the remove task replies.
+    #[msg(exec)]
+    pub fn teardown(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+        limit: Option<u32>,
+    ) -> Result<Response, ContractError> {
+        let (mut deps, env, info) = ctx;
+
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        self.terminated.save(deps.storage, &true)?;
+
+        // Actions are removed by the end of the call, so each call starts from the first one
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+        let mut actions = self
+            .actions
+            .range(deps.storage, None, None, Order::Ascending)
+            .take(limit + 1)
+            .collect::<StdResult<Vec<_>>>()?;
+        let completed = actions.len() <= limit;
+        actions.truncate(limit);
+
+        let mut croncat_addrs = CroncatAddrs::new();
+        let mut msgs = vec![];
+        for (id, action) in actions {
+            if let Some(task_hash) = &action.task_hash {
+                let task_addr =
+                    self.cached_contract_addr(&deps.as_ref(), &mut croncat_addrs, &action, TASK)?;
+                let croncat_task = deps
+                    .querier
+                    .query_wasm_smart::<TaskResponse>(
+                        task_addr,
+                        &CCTaskQueryMsg::Task {
+                            task_hash: task_hash.clone(),
+                        },
+                    )?
+                    .task;
+
+                // The action is removed in the reply
+                if croncat_task.is_some() {
+                    msgs.push(self.remove_task_submsg(deps.as_ref(), id)?);
+                    continue;
+                }
+                self.task_hashes.remove(deps.storage, task_hash);
+            }
+            self.remove_action(deps.storage, id)?;
+        }
+
+        let event = Event::new("vectis.cronkitty.v1.MsgTeardown")
+            .add_attribute("Tasks Removed", msgs.len().to_string())
+            .add_attribute("Completed", completed.to_string());
+        let mut res = self.refund_owner(deps.as_ref(), &env, None)?;
+        for manager in self.cw20_deposit_managers(deps.storage)? {
+            res = self.withdraw_cw20(deps.branch(), &env, &manager, res)?;
+        }
+        Ok(res.add_event(event).add_submessages(msgs))
+    }
+
+    /// Sets the croncat factory new tasks are created on,
+    /// existing actions stay on the factory they were created on
+    #[msg(exec)]
+    pub fn update_croncat_factory(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+        addr: String,
+    ) -> Result<Response, ContractError> {
+        let (deps, _env, info) = ctx;
+
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let croncat_factory = self.validate_croncat_factory(&deps.as_ref(), &addr)?;
+        self.croncat_factory.save(deps.storage, &croncat_factory)?;
+
+        Ok(Response::new().add_event(
+            Event::new("vectis.cronkitty.v1.MsgUpdateCroncatFactory")
+                .add_attribute("Croncat Factory", addr),
+        ))
+    }
+
+    /// Forwards native balances held by this contract to the owner (proxy),
+    /// all denoms are forwarded if `denoms` is not provided
+    #[msg(exec)]
+    pub fn sweep_funds(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+        denoms: Option<Vec<String>>,
+    ) -> Result<Response, ContractError> {
+        let (deps, env, info) = ctx;
+
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        self.refund_owner(deps.as_ref(), &env, denoms)
+    }
+
+    /// Forwards cw20 held for this contract on croncat managers to the owner (proxy),
+    /// i.e. unused deposits and refunds on the latest manager and on managers with deposits
+    #[msg(exec)]
+    pub fn sweep_cw20_funds(
+        &self,
+        ctx: (DepsMut, Env, MessageInfo),
+    ) -> Result<Response, ContractError> {
+        let (mut deps, env, info) = ctx;
+
+        if info.sender != deps.api.addr_humanize(&self.owner.load(deps.storage)?)? {
+            return Err(ContractError::Unauthorized);
+        }
+
+        let croncat_factory = self.croncat_factory.load(deps.storage)?;
+        let version =
+            self.query_latest_version_croncat_contract(&deps.as_ref(), &croncat_factory, MANAGER)?;
+        let mut managers = self.cw20_deposit_managers(deps.storage)?;
+        managers.insert(self.query_contract_addr(
+            &deps.as_ref(),
+            &croncat_factory,
+            &version,
+            MANAGER,
+        )?);
+
+        let mut res = Response::new();
+        for manager in managers {
+            res = self.withdraw_cw20(deps.branch(), &env, &manager, res)?;
+        }
+        Ok(res)
+    }
+
+    #[msg(query)]
+    pub fn action_id(&self, ctx: (Deps, Env)) -> StdResult<u64> {
+        let (deps, _) = ctx;
+        self.next_action_id.load(deps.storage)
+    }
+
+    /// The budget and what was sent within its window
+    #[msg(query)]
+    pub fn budget(&self, ctx: (Deps, Env)) -> Result<CronKittyBudgetResp, ContractError> {
+        let (deps, env) = ctx;
+        let budget = match self.budget.may_load(deps.storage)? {
+            Some(budget) => budget,
+            None => {
+                return Ok(CronKittyBudgetResp {
+                    budget: None,
+                    spent: vec![],
+                    remaining: vec![],
+                })
+            }
+        };
+
+        let spent = self.budget_spent(deps.storage, &env, &budget)?;
+        let remaining = budget
+            .limit
+            .iter()
+            .map(|c| Coin {
+                denom: c.denom.clone(),
+                amount: c.amount.saturating_sub(amount_of(&spent, &c.denom)),
+            })
+            .collect();
+
+        Ok(CronKittyBudgetResp {
+            budget: Some(budget),
+            spent,
+            remaining,
+        })
+    }
+
+    #[msg(query)]
+    pub fn config(&self, ctx: (Deps, Env)) -> Result<CronKittyConfigResp, ContractError> {
+        let (deps, _) = ctx;
+
+        let croncat_factory = self.croncat_factory.load(deps.storage)?;
+        Ok(CronKittyConfigResp {
+            owner: deps.api.addr_humanize(&self.owner.load(deps.storage)?)?,
+            croncat_factory: deps.api.addr_humanize(&croncat_factory)?,
+            next_action_id: self.next_action_id.load(deps.storage)?,
+            active_actions: self
+                .active_actions
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+            paused_actions: self
+                .paused_actions
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+            contract_version: get_contract_version(deps.storage)?,
+            croncat_tasks_version: self
+                .query_latest_version_croncat_contract(&deps, &croncat_factory, TASK)
+                .ok(),
+            croncat_manager_version: self
+                .query_latest_version_croncat_contract(&deps, &croncat_factory, MANAGER)
+                .ok(),
+            terminated: self.terminated.may_load(deps.storage)?.unwrap_or(false),
+        })
+    }
+
+    // These are the id that stores the actual cosmos messages
+    #[msg(query)]
+    pub fn action(
+        &self,
+        ctx: (Deps, Env),
+        action_id: u64,
+    ) -> Result<CronKittyActionResp, ContractError> {
+        let (deps, _) = ctx;
+        let action = self.actions.load(deps.storage, action_id)?;
+        let task_addr =
+            self.query_contract_addr(&deps, &action.croncat_factory, &action.version, TASK)?;
+        let manager_addr =
+            self.query_contract_addr(&deps, &action.croncat_factory, &action.version, MANAGER)?;
+        Ok(CronKittyActionResp::new(action, task_addr, manager_addr))
+    }
+
+    /// Predicts the next executions of an active action after the current block,
+    /// up to `count` of them and within the task boundary
+    #[msg(query)]
+    pub fn next_executions(
+        &self,
+        ctx: (Deps, Env),
+        action_id: u64,
+        count: Option<u32>,
+    ) -> Result<Vec<CronKittyExecutionSlot>, ContractError> {
+        let (deps, env) = ctx;
+        let count = count.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let action = self.actions.load(deps.storage, action_id)?;
+        if action.status != ActionStatus::Active {
+            return Ok(vec![]);
+        }
+        let task_hash = action
+            .task_hash
+            .clone()
+            .ok_or(ContractError::TaskHashNotFound)?;
+
+        let manager_addr =
+            self.query_contract_addr(&deps, &action.croncat_factory, &action.version, MANAGER)?;
+        // Only tells if the task was executed when it is the last one croncat executed,
+        // which is enough for `Interval::Once` as croncat removes the task afterwards
+        let executed = self
+            .last_task_execution_info
+            .query(&deps.querier, manager_addr)
+            .map_or(false, |info| info.task_hash == task_hash);
+
+        let schedule = self.action_schedule(&deps, &action, task_hash)?;
+        next_executions(&schedule, &env.block, executed, count)
+    }
+
+    /// The latest executions of the action, newest first
+    #[msg(query)]
+    pub fn execution_history(
+        &self,
+        ctx: (Deps, Env),
+        action_id: u64,
+        limit: Option<u32>,
+    ) -> Result<CronKittyExecutionHistoryResp, ContractError> {
+        let (deps, _) = ctx;
+        let limit = (limit.unwrap_or(DEFAULT_LIMIT) as u64).min(HISTORY_SIZE);
+        let executions = self
+            .execution_counts
+            .may_load(deps.storage, action_id)?
+            .unwrap_or_default();
+
+        let history = (executions.saturating_sub(limit)..executions)
+            .rev()
+            .map(|n| {
+                self.execution_history
+                    .load(deps.storage, (action_id, n % HISTORY_SIZE))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(CronKittyExecutionHistoryResp {
+            executions,
+            history,
+        })
+    }
+
+    /// The balance of the action's task on croncat
+    #[msg(query)]
+    pub fn task_balance(
+        &self,
+        ctx: (Deps, Env),
+        action_id: u64,
+    ) -> Result<CronKittyTaskBalanceResp, ContractError> {
+        let (deps, _) = ctx;
+        let action = self.actions.load(deps.storage, action_id)?;
+        let task_hash = action.task_hash.ok_or(ContractError::TaskHashNotFound)?;
+        let task_addr =
+            self.query_contract_addr(&deps, &action.croncat_factory, &action.version, TASK)?;
+        let manager_addr =
+            self.query_contract_addr(&deps, &action.croncat_factory, &action.version, MANAGER)?;
+
+        let balance = deps
+            .querier
+            .query_wasm_smart::<TaskBalanceResponse>(
+                &manager_addr,
+                &CCManagerQueryMsg::TaskBalance {
+                    task_hash: task_hash.clone(),
+                },
+            )?
+            .balance;
+        let task = deps
+            .querier
+            .query_wasm_smart::<TaskResponse>(&task_addr, &CCTaskQueryMsg::Task { task_hash })?
+            .task;
+
+        let executions_remaining = match (&balance, task) {
+            (Some(balance), Some(task)) => {
+                let config = self.manager_config.query(&deps.querier, manager_addr)?;
+                let cost = native_cost_per_execution(task.amount_for_one_task.gas, &config)?;
+                if cost.is_zero() {
+                    None
+                } else {
+                    Some(u64::try_from((balance.native_balance / cost).u128()).unwrap_or(u64::MAX))
+                }
+            }
+            _ => None,
+        };
+
+        Ok(CronKittyTaskBalanceResp {
+            balance,
+            executions_remaining,
+        })
+    }
+
+    /// The native deposit required to create `task` on the latest croncat version
+    #[msg(query)]
+    pub fn estimate_task_cost(
+        &self,
+        ctx: (Deps, Env),
+        task: TaskRequest,
+        executions: Option<u64>,
+    ) -> Result<CronKittyTaskCostResp, ContractError> {
+        let (deps, _) = ctx;
+        let croncat_factory = self.croncat_factory.load(deps.storage)?;
+        let version = self.query_latest_version_croncat_contract(&deps, &croncat_factory, TASK)?;
+        self.task_cost(&deps, &croncat_factory, &version, &task, executions)
+    }
+
+    /// The action of a croncat task created by this contract
+    #[msg(query)]
+    pub fn action_by_task_hash(
+        &self,
+        ctx: (Deps, Env),
+        task_hash: String,
+    ) -> Result<(u64, CronKittyActionResp), ContractError> {
+        let (deps, env) = ctx;
+        let action_id = self
+            .task_hashes
+            .may_load(deps.storage, &task_hash)?
+            .ok_or(ContractError::TaskNotFound)?;
+        Ok((action_id, self.action((deps, env), action_id)?))
+    }
+
+    /// Lists actions in ascending `action_id` order.
+    /// `Pending` actions, i.e. the creation on croncat did not complete,
+    /// are skipped unless `include_pending` is set.
+    #[msg(query)]
+    pub fn list_actions(
+        &self,
+        ctx: (Deps, Env),
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        include_pending: Option<bool>,
+    ) -> Result<Vec<(u64, CronKittyActionResp)>, ContractError> {
+        let (deps, _) = ctx;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let include_pending = include_pending.unwrap_or(false);
+        let start = start_after.map(Bound::exclusive);
+
+        let mut croncat_addrs = CroncatAddrs::new();
+
+        self.actions
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|r| {
+                include_pending
+                    || !matches!(r, Ok((_, action)) if action.status == ActionStatus::Pending)
             })
+            .take(limit)
+            .map(|r| -> Result<(u64, CronKittyActionResp), ContractError> {
+                let (id, action) = r?;
+                let task_addr =
+                    self.cached_contract_addr(&deps, &mut croncat_addrs, &action, TASK)?;
+                let manager_addr =
+                    self.cached_contract_addr(&deps, &mut croncat_addrs, &action, MANAGER)?;
+                Ok((
+                    id,
+                    CronKittyActionResp::new(action, task_addr, manager_addr),
+                ))
+            })
+            .collect()
+    }
+
+    #[msg(migrate)]
+    fn migrate(
+        &self,
+        ctx: (DepsMut, Env),
+        croncat_factory_addr: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let (mut deps, env) = ctx;
+        let stored = get_contract_version(deps.storage)?;
+        if stored.contract != CONTRACT_NAME {
+            return Err(ContractError::InvalidMigration {
+                contract: stored.contract,
+                version: stored.version,
+            });
+        }
+
+        let stored_version = parse_version(&stored.version)?;
+        if stored_version > parse_version(CONTRACT_VERSION)? {
+            return Err(ContractError::MigrationDowngrade {
+                from: stored.version,
+                to: CONTRACT_VERSION.to_string(),
+            });
+        }
+
+        for (version, transform) in STATE_TRANSFORMS {
+            if stored_version < parse_version(version)? {
+                transform(deps.branch(), &env)?;
+            }
+        }
+
+        if let Some(addr) = croncat_factory_addr {
+            let croncat_factory = self.validate_croncat_factory(&deps.as_ref(), &addr)?;
+            self.croncat_factory.save(deps.storage, &croncat_factory)?;
+        }
+
+        set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+        Ok(Response::new().add_event(
+            Event::new("vectis.cronkitty.v1.MsgMigrate")
+                .add_attribute("From Version", stored.version)
+                .add_attribute("To Version", CONTRACT_VERSION),
+        ))
+    }
+
+    /// Saves a new action with the next action id and returns the submsg creating its task on
+    /// croncat, the task hash is set in the reply
+    fn create_task_submsg(
+        &self,
+        deps: DepsMut,
+        env: &Env,
+        mut task: TaskRequest,
+        label: Option<String>,
+        funds: Vec<Coin>,
+    ) -> Result<SubMsg, ContractError> {
+        if self.terminated.may_load(deps.storage)?.unwrap_or(false) {
+            return Err(ContractError::Terminated);
+        }
+
+        // guarenteed by croncat that TASK and MANAGER are the same version
+        let croncat_factory = self.croncat_factory.load(deps.storage)?;
+        let contract_version =
+            self.query_latest_version_croncat_contract(&deps.as_ref(), &croncat_factory, TASK)?;
+        let task_contract_addr =
+            self.query_contract_addr(&deps.as_ref(), &croncat_factory, &contract_version, TASK)?;
+
+        // The id for croncat to call back, allocated here so that several tasks can be created
+        // in the same transaction
+        let id = self.next_action_id.load(deps.storage)?;
+        self.next_action_id.save(
+            deps.storage,
+            &id.checked_add(1).ok_or(ContractError::Overflow)?,
+        )?;
+        self.save_action(
+            deps.storage,
+            id,
+            &CronKittyAction {
+                version: contract_version,
+                croncat_factory: croncat_factory.clone(),
+                msgs: task.actions.iter().cloned().map(|a| a.msg).collect(),
+                task_hash: None,
+                created_at: Some(env.block.time),
+                status: ActionStatus::Pending,
+                label,
+                schedule: Some(CronKittySchedule::from_task(&task)?),
+                failure_limit: None,
+                consecutive_failures: 0,
+                spend_limit: None,
+                spent: vec![],
+            },
+        )?;
+
+        // This sums up all the action gas into one because croncat manager will only know the
+        // action id on this contract to call.
+        // Each task's gas_limit is provided by simulation in the frontend on croncat
+        // `EstimateTaskCost` applies the same aggregation to provide the required funds.
+        //
+        // Croncat logic:
+        // Task contract calculates the total gas specified by user for each task,
+        // it then creates fund balance for the task on the manager (who holds the funds sent)
+        // On storing the task balance, the manager checks there is enough funds (gas_limit,
+        // fees for croncat, native, cw20, ibc, etc)
+        // The required fee per action is gas_base_fee + gas_action_fee + gas_limit +
+        // treasury_fee + agent_fee. If it is not a one-off task, the fees are multipled by 2.
+        //
+        // Since Vectis Accounts will be self-custody, croncat only need to check that the gas is
+        // enough. This is calculated in `execute_create_task_balance` on the manager
+        // We check it here first so that the error is not an opaque one from the manager
+
+        let required = self
+            .task_cost(
+                &deps.as_ref(),
+                &croncat_factory,
+                &contract_version,
+                &task,
+                None,
+            )?
+            .min_deposit;
+        let provided = Coin {
+            amount: funds
+                .iter()
+                .filter(|c| c.denom == required.denom)
+                .map(|c| c.amount)
+                .sum(),
+            denom: required.denom.clone(),
+        };
+        if provided.amount < required.amount {
+            return Err(ContractError::NotEnoughFundsForGas { required, provided });
+        }
+
+        let gas_limit = collapse_gas_limit(&task.actions)?;
+
+        // This is the action stored on Croncat contract
+        let action = croncat_action(env, id, gas_limit)?;
+
+        // We forward all the other params (so we can contribute to / use to frontend code from
+        // croncat)
+        // The Action called is to call this plugin at the given intervals
+        task.actions = vec![action];
+
+        // cw20 for the task is taken from the deposit on the croncat manager of this version
+        if let Some(cw20) = &task.cw20 {
+            let token = deps.api.addr_validate(&cw20.address)?;
+            let manager = self.query_contract_addr(
+                &deps.as_ref(),
+                &croncat_factory,
+                &contract_version,
+                MANAGER,
+            )?;
+            self.cw20_deposits.update(
+                deps.storage,
+                (&manager, &token),
+                |deposit| -> Result<Uint128, ContractError> {
+                    deposit
+                        .unwrap_or_default()
+                        .checked_sub(cw20.amount)
+                        .map_err(|_| ContractError::InsufficientCw20Deposit {
+                            token: token.to_string(),
+                        })
+                },
+            )?;
+        }
+
+        Ok(SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: task_contract_addr.to_string(),
+                msg: to_binary(&CCTaskExecMsg::CreateTask {
+                    task: Box::new(task),
+                })?,
+                // TODO: This is the value the user provides for the task execution.
+                // https://github.com/CronCats/cw-croncat/issues/204
+                funds,
+            }),
+            ReplyOp::CreateTask.reply_id(id)?,
+        ))
+    }
+
+    /// The msgs to remove the task of the action and recreate it on the latest croncat version,
+    /// `None` if it is already on the latest version
+    fn migrate_action_msgs(
+        &self,
+        deps: DepsMut,
+        env: &Env,
+        action_id: u64,
+        funds: Vec<Coin>,
+    ) -> Result<Option<(CosmosMsg, SubMsg, Event)>, ContractError> {
+        let mut action = self.actions.load(deps.storage, action_id)?;
+        let task_hash = action
+            .task_hash
+            .clone()
+            .ok_or(ContractError::TaskHashNotFound)?;
+
+        let croncat_factory = self.croncat_factory.load(deps.storage)?;
+        let latest_version =
+            self.query_latest_version_croncat_contract(&deps.as_ref(), &croncat_factory, TASK)?;
+        if action.croncat_factory == croncat_factory && action.version == latest_version {
+            return Ok(None);
+        }
+
+        let task_addr = self.query_contract_addr(
+            &deps.as_ref(),
+            &action.croncat_factory,
+            &action.version,
+            TASK,
+        )?;
+        let manager_addr = self.query_contract_addr(
+            &deps.as_ref(),
+            &action.croncat_factory,
+            &action.version,
+            MANAGER,
+        )?;
+        let latest_task_addr =
+            self.query_contract_addr(&deps.as_ref(), &croncat_factory, &latest_version, TASK)?;
+
+        let schedule = self.action_schedule(&deps.as_ref(), &action, task_hash.clone())?;
+
+        let (funds, cw20) = self.task_refund(&deps.as_ref(), &manager_addr, &task_hash, funds)?;
+        // cw20 is refunded to the temp balance of this contract on the old manager
+        if cw20.map_or(false, |c| !c.amount.is_zero()) {
+            return Err(ContractError::Cw20TaskNotMigratable);
+        }
+
+        let task = schedule.to_task(croncat_action(env, action_id, schedule.gas_limit)?, None);
+
+        self.task_hashes.remove(deps.storage, &task_hash);
+        let remove_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: task_addr.to_string(),
+            msg: to_binary(&CCTaskExecMsg::RemoveTask { task_hash })?,
+            funds: vec![],
+        });
+        let create_msg = SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: latest_task_addr.to_string(),
+                msg: to_binary(&CCTaskExecMsg::CreateTask {
+                    task: Box::new(task),
+                })?,
+                funds,
+            }),
+            ReplyOp::CreateTask.reply_id(action_id)?,
+        );
+
+        let event = Event::new("vectis.cronkitty.v1.MsgMigrateActionToLatest")
+            .add_attribute("Task ID", action_id.to_string())
+            .add_attribute(
+                "From Version",
+                format!("{}.{}", action.version[0], action.version[1]),
+            )
+            .add_attribute(
+                "To Version",
+                format!("{}.{}", latest_version[0], latest_version[1]),
+            );
+
+        // The new task hash is set in the reply, paused actions stay paused
+        action.version = latest_version;
+        action.croncat_factory = croncat_factory;
+        action.schedule = Some(schedule);
+        action.task_hash = None;
+        if action.status == ActionStatus::Active {
+            action.status = ActionStatus::Pending;
+        }
+        self.save_action(deps.storage, action_id, &action)?;
+
+        Ok(Some((remove_msg, create_msg, event)))
+    }
+
+    /// Removing a task refunds its native balance to this contract (task owner),
+    /// this adds the refund to `funds` to be sent along with the recreated task.
+    /// Also returns the cw20 balance of the task.
+    fn task_refund(
+        &self,
+        deps: &Deps,
+        manager_addr: &Addr,
+        task_hash: &str,
+        mut funds: Vec<Coin>,
+    ) -> Result<(Vec<Coin>, Option<Cw20CoinVerified>), ContractError> {
+        let task_balance = deps
+            .querier
+            .query_wasm_smart::<TaskBalanceResponse>(
+                manager_addr,
+                &CCManagerQueryMsg::TaskBalance {
+                    task_hash: task_hash.to_string(),
+                },
+            )?
+            .balance;
+        let (refund, cw20) = match task_balance {
+            Some(b) => (b.native_balance, b.cw20_balance),
+            None => (Uint128::zero(), None),
+        };
+
+        if !refund.is_zero() {
+            let native_denom = self
+                .manager_config
+                .query(&deps.querier, manager_addr.clone())?
+                .native_denom;
+            match funds.iter_mut().find(|c| c.denom == native_denom) {
+                Some(c) => c.amount += refund,
+                None => funds.push(Coin {
+                    denom: native_denom,
+                    amount: refund,
+                }),
+            }
+        }
+        Ok((funds, cw20))
+    }
+
+    /// The stored schedule of the action,
+    /// actions created before the schedule was stored are read from croncat
+    fn action_schedule(
+        &self,
+        deps: &Deps,
+        action: &CronKittyAction,
+        task_hash: String,
+    ) -> Result<CronKittySchedule, ContractError> {
+        if let Some(schedule) = &action.schedule {
+            return Ok(schedule.clone());
+        }
+        let task_addr =
+            self.query_contract_addr(deps, &action.croncat_factory, &action.version, TASK)?;
+        let croncat_task = deps
+            .querier
+            .query_wasm_smart::<TaskResponse>(task_addr, &CCTaskQueryMsg::Task { task_hash })?
+            .task
+            .ok_or(ContractError::TaskNotFound)?;
+        CronKittySchedule::from_task_info(croncat_task)
+    }
+
+    /// The submsg removing the task of the action on croncat, `None` for an `Ended` action
+    /// whose task croncat has already removed, which is then removed here
+    fn remove_task_or_ended_action(
+        &self,
+        deps: DepsMut,
+        action_id: u64,
+    ) -> Result<Option<SubMsg>, ContractError> {
+        let action = self.actions.load(deps.storage, action_id)?;
+        if action.task_hash.is_none() && action.status == ActionStatus::Ended {
+            self.remove_action(deps.storage, action_id)?;
+            return Ok(None);
+        }
+        self.remove_task_submsg(deps.as_ref(), action_id).map(Some)
+    }
+
+    /// The submsg removing the task of the action on croncat,
+    /// the action is removed in the reply
+    fn remove_task_submsg(&self, deps: Deps, action_id: u64) -> Result<SubMsg, ContractError> {
+        if let CronKittyAction {
+            version,
+            croncat_factory,
+            task_hash: Some(task_hash),
+            ..
+        } = self.actions.load(deps.storage, action_id)?
+        {
+            let task = self.query_contract_addr(&deps, &croncat_factory, &version, TASK)?;
+            Ok(SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: task.to_string(),
+                    msg: to_binary(&CCTaskExecMsg::RemoveTask { task_hash })?,
+                    funds: vec![],
+                }),
+                ReplyOp::RemoveTask.reply_id(action_id)?,
+            ))
+        } else {
+            Err(ContractError::TaskHashNotFound)
+        }
+    }
+
+    /// Increments the executions of the action and records this one in its history,
+    /// returns the number of executions
+    pub(crate) fn record_execution(
+        &self,
+        deps: DepsMut,
+        env: &Env,
+        action_id: u64,
+        task_execution_info: TaskExecutionInfo,
+    ) -> Result<u64, ContractError> {
+        let n = self
+            .execution_counts
+            .may_load(deps.storage, action_id)?
+            .unwrap_or_default();
+        self.execution_history.save(
+            deps.storage,
+            (action_id, n % HISTORY_SIZE),
+            &CronKittyExecutionRecord {
+                height: env.block.height,
+                time: env.block.time,
+                task_execution_info,
+                outcome: None,
+            },
+        )?;
+        let executions = n.checked_add(1).ok_or(ContractError::Overflow)?;
+        self.execution_counts
+            .save(deps.storage, action_id, &executions)?;
+        Ok(executions)
+    }
+
+    /// Records the success of the latest execution and what its msgs sent,
+    /// and resets the consecutive failures
+    pub(crate) fn execution_succeeded(
+        &self,
+        deps: DepsMut,
+        env: &Env,
+        action_id: u64,
+    ) -> Result<Response, ContractError> {
+        self.record_execution_outcome(deps.storage, action_id, CronKittyExecutionOutcome::Success)?;
+        let mut action = self.actions.load(deps.storage, action_id)?;
+        action.consecutive_failures = 0;
+        // Unmetered msgs are only executed without caps, nothing is counted
+        let spend = msgs_spend(&action.msgs)?.unwrap_or_default();
+        action.spent = add_coins(&action.spent, &spend)?;
+        self.save_action(deps.storage, action_id, &action)?;
+
+        if !spend.is_empty() && self.budget.may_load(deps.storage)?.is_some() {
+            self.budget_spends.push_back(
+                deps.storage,
+                &CronKittyBudgetSpend {
+                    time: env.block.time,
+                    spent: add_coins(&[], &spend)?,
+                },
+            )?;
+        }
+        Ok(Response::new())
+    }
+
+    /// Sums up the spends within the budget window ending at the current block
+    fn budget_spent(
+        &self,
+        storage: &dyn Storage,
+        env: &Env,
+        budget: &CronKittyBudget,
+    ) -> StdResult<Vec<Coin>> {
+        let mut spent = vec![];
+        for spend in self.budget_spends.iter(storage)? {
+            let spend = spend?;
+            if env.block.time < spend.time.plus_seconds(budget.window) {
+                spent = add_coins(&spent, &sum_coins(spend.spent.iter())?)?;
+            }
+        }
+        Ok(spent)
+    }
+
+    /// Drops the spends that have left the budget window
+    fn prune_budget_spends(
+        &self,
+        storage: &mut dyn Storage,
+        env: &Env,
+        budget: &CronKittyBudget,
+    ) -> StdResult<()> {
+        while let Some(spend) = self.budget_spends.front(storage)? {
+            if env.block.time < spend.time.plus_seconds(budget.window) {
+                break;
+            }
+            self.budget_spends.pop_front(storage)?;
+        }
+        Ok(())
+    }
+
+    /// Records the failure of the latest execution,
+    /// pauses or removes the action once its failure limit is reached
+    pub(crate) fn execution_failed(
+        &self,
+        deps: DepsMut,
+        action_id: u64,
+        error: String,
+    ) -> Result<Response, ContractError> {
+        self.record_execution_outcome(
+            deps.storage,
+            action_id,
+            CronKittyExecutionOutcome::Failure {
+                error: error.clone(),
+            },
+        )?;
+        let mut res = Response::new().add_event(
+            Event::new("vectis.cronkitty.v1.ReplyProxyExecuteFailed")
+                .add_attribute("Task ID", action_id.to_string())
+                .add_attribute("Error", error),
+        );
+
+        let mut action = self.actions.load(deps.storage, action_id)?;
+        action.consecutive_failures = action.consecutive_failures.saturating_add(1);
+        match &action.failure_limit {
+            Some(limit) if action.consecutive_failures >= limit.max_consecutive_failures => {
+                let event = Event::new("vectis.cronkitty.v1.FailureLimitReached")
+                    .add_attribute("Task ID", action_id.to_string())
+                    .add_attribute("Failures", action.consecutive_failures.to_string());
+                action.consecutive_failures = 0;
+                match limit.on_limit {
+                    CronKittyOnFailureLimit::Pause => {
+                        if action.status == ActionStatus::Active {
+                            action.status = ActionStatus::Paused;
+                        }
+                        self.save_action(deps.storage, action_id, &action)?;
+                        res = res.add_event(event.add_attribute("Action", "pause"));
+                    }
+                    CronKittyOnFailureLimit::Remove => {
+                        // This runs within the croncat execution of the task,
+                        // the task is removed by `sync_actions`
+                        action.status = ActionStatus::Failed;
+                        self.save_action(deps.storage, action_id, &action)?;
+                        res = res.add_event(event.add_attribute("Action", "pending_removal"));
+                    }
+                }
+            }
+            _ => self.save_action(deps.storage, action_id, &action)?,
+        }
+
+        Ok(res)
+    }
+
+    /// Sets the outcome of the latest execution of the action
+    fn record_execution_outcome(
+        &self,
+        storage: &mut dyn Storage,
+        action_id: u64,
+        outcome: CronKittyExecutionOutcome,
+    ) -> Result<(), ContractError> {
+        let n = self
+            .execution_counts
+            .load(storage, action_id)?
+            .checked_sub(1)
+            .ok_or(ContractError::Overflow)?;
+        let key = (action_id, n % HISTORY_SIZE);
+        let mut record = self.execution_history.load(storage, key)?;
+        record.outcome = Some(outcome);
+        self.execution_history.save(storage, key, &record)?;
+        Ok(())
+    }
+
+    /// Saves the action, moving it between the status counts if its status changed
+    pub(crate) fn save_action(
+        &self,
+        storage: &mut dyn Storage,
+        action_id: u64,
+        action: &CronKittyAction,
+    ) -> StdResult<()> {
+        let prev = self.actions.may_load(storage, action_id)?.map(|a| a.status);
+        self.update_status_counts(storage, prev.as_ref(), Some(&action.status))?;
+        self.actions.save(storage, action_id, action)
+    }
+
+    /// Moves an action from the count of its previous status to that of its new status,
+    /// `None` for an action that is created / removed
+    pub(crate) fn update_status_counts(
+        &self,
+        storage: &mut dyn Storage,
+        from: Option<&ActionStatus>,
+        to: Option<&ActionStatus>,
+    ) -> StdResult<()> {
+        if from == to {
+            return Ok(());
+        }
+        if let Some(count) = from.and_then(|s| self.status_count(s)) {
+            let n = count.may_load(storage)?.unwrap_or_default();
+            count.save(storage, &n.saturating_sub(1))?;
+        }
+        if let Some(count) = to.and_then(|s| self.status_count(s)) {
+            let n = count.may_load(storage)?.unwrap_or_default();
+            count.save(storage, &(n + 1))?;
+        }
+        Ok(())
+    }
+
+    fn status_count(&self, status: &ActionStatus) -> Option<&Item<'_, u64>> {
+        match status {
+            ActionStatus::Active => Some(&self.active_actions),
+            ActionStatus::Paused => Some(&self.paused_actions),
+            ActionStatus::Pending | ActionStatus::Ended | ActionStatus::Failed => None,
+        }
+    }
+
+    /// Removes the action along with its execution history
+    pub(crate) fn remove_action(&self, storage: &mut dyn Storage, action_id: u64) -> StdResult<()> {
+        let prev = self.actions.may_load(storage, action_id)?.map(|a| a.status);
+        self.update_status_counts(storage, prev.as_ref(), None)?;
+        self.actions.remove(storage, action_id);
+        self.execution_counts.remove(storage, action_id);
+        for slot in 0..HISTORY_SIZE {
+            self.execution_history.remove(storage, (action_id, slot));
+        }
+        Ok(())
+    }
+
+    /// Croncat refunds the task owner, i.e. this contract, on removing a task or when it runs
+    /// out of balance. This sends the native balances held here back to the owner (proxy).
+    pub(crate) fn refund_owner(
+        &self,
+        deps: Deps,
+        env: &Env,
+        denoms: Option<Vec<String>>,
+    ) -> Result<Response, ContractError> {
+        let balances = match denoms {
+            Some(denoms) => denoms
+                .into_iter()
+                .map(|denom| deps.querier.query_balance(&env.contract.address, denom))
+                .collect::<StdResult<Vec<Coin>>>()?,
+            None => deps.querier.query_all_balances(&env.contract.address)?,
+        };
+        let amount: Vec<Coin> = balances
+            .into_iter()
+            .filter(|c| !c.amount.is_zero())
+            .collect();
+
+        if amount.is_empty() {
+            return Ok(Response::new());
+        }
+
+        let owner = deps
+            .api
+            .addr_humanize(&self.owner.load(deps.storage)?)?
+            .into_string();
+        let event = Event::new("vectis.cronkitty.v1.RefundOwner")
+            .add_attribute("Proxy", owner.clone())
+            .add_attribute(
+                "Amount",
+                amount
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: owner,
+                amount,
+            })
+            .add_event(event))
+    }
+
+    /// Croncat keeps cw20 refunds and unused deposits in the temp balance of this contract on the
+    /// manager. This withdraws them and forwards them to the owner (proxy) along with `res`.
+    pub(crate) fn withdraw_cw20(
+        &self,
+        deps: DepsMut,
+        env: &Env,
+        manager: &Addr,
+        res: Response,
+    ) -> Result<Response, ContractError> {
+        // Deposits are withdrawn along with the refunds
+        let tokens = self
+            .cw20_deposits
+            .prefix(manager)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for token in tokens {
+            self.cw20_deposits.remove(deps.storage, (manager, &token));
+        }
+
+        let balances: Vec<Cw20CoinVerified> = deps.querier.query_wasm_smart(
+            manager,
+            &CCManagerQueryMsg::UsersBalances {
+                address: env.contract.address.to_string(),
+                from_index: None,
+                limit: None,
+            },
+        )?;
+        let balances: Vec<Cw20CoinVerified> = balances
+            .into_iter()
+            .filter(|c| !c.amount.is_zero())
+            .collect();
+        if balances.is_empty() {
+            return Ok(res);
+        }
+
+        let owner = deps
+            .api
+            .addr_humanize(&self.owner.load(deps.storage)?)?
+            .into_string();
+        let withdraw_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: manager.to_string(),
+            msg: to_binary(&CCManagerExecMsg::UserWithdraw {
+                limit: Some(balances.len() as u64),
+            })?,
+            funds: vec![],
+        });
+        let transfer_msgs = balances
+            .iter()
+            .map(|c| -> StdResult<CosmosMsg> {
+                Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: c.address.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: owner.clone(),
+                        amount: c.amount,
+                    })?,
+                    funds: vec![],
+                }))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        let event = Event::new("vectis.cronkitty.v1.RefundOwnerCw20")
+            .add_attribute("Proxy", owner)
+            .add_attribute("Manager", manager)
+            .add_attribute(
+                "Amount",
+                balances
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+
+        Ok(res
+            .add_message(withdraw_msg)
+            .add_messages(transfer_msgs)
+            .add_event(event))
+    }
+
+    /// The croncat managers holding cw20 deposited for tasks not yet created
+    fn cw20_deposit_managers(&self, storage: &dyn Storage) -> StdResult<BTreeSet<Addr>> {
+        self.cw20_deposits
+            .keys(storage, None, None, Order::Ascending)
+            .map(|k| k.map(|(manager, _)| manager))
+            .collect()
+    }
+
+    /// Reads the croncat tasks and manager config to calculate the native cost of `task`
+    /// once its actions are collapsed into the one calling this contract
+    pub(crate) fn task_cost(
+        &self,
+        deps: &Deps,
+        croncat_factory: &CanonicalAddr,
+        version: &[u8; 2],
+        task: &TaskRequest,
+        executions: Option<u64>,
+    ) -> Result<CronKittyTaskCostResp, ContractError> {
+        let tasks_config = self.tasks_config.query(
+            &deps.querier,
+            self.query_contract_addr(deps, croncat_factory, version, TASK)?,
+        )?;
+        let manager_config = self.manager_config.query(
+            &deps.querier,
+            self.query_contract_addr(deps, croncat_factory, version, MANAGER)?,
+        )?;
+
+        let gas = task_gas(task, &tasks_config)?;
+        let cost_per_execution = native_cost_per_execution(gas, &manager_config)?;
+        let min_deposit = min_deposit(cost_per_execution, &task.interval)?;
+        let cost_for_executions = executions
+            .map(|n| {
+                cost_per_execution
+                    .checked_mul(Uint128::from(n))
+                    .map_err(|_| ContractError::Overflow)
+            })
+            .transpose()?;
+
+        let denom = manager_config.native_denom;
+        Ok(CronKittyTaskCostResp {
+            gas,
+            cost_per_execution: Coin {
+                denom: denom.clone(),
+                amount: cost_per_execution,
+            },
+            min_deposit: Coin {
+                denom: denom.clone(),
+                amount: min_deposit,
+            },
+            cost_for_executions: cost_for_executions.map(|amount| Coin { denom, amount }),
+        })
+    }
+
+    /// Sends cw20 to the temp balance of this contract on the croncat manager
+    fn cw20_to_manager_msg(
+        &self,
+        token: &Addr,
+        manager: &Addr,
+        amount: Uint128,
+    ) -> Result<CosmosMsg, ContractError> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: manager.to_string(),
+                amount,
+                msg: to_binary(&CCManagerReceiveMsg::RefillTempBalance {})?,
+            })?,
+            funds: vec![],
+        }))
+    }
+
+    /// A croncat factory must have latest versions of both the tasks and manager contracts
+    fn validate_croncat_factory(
+        &self,
+        deps: &Deps,
+        addr: &str,
+    ) -> Result<CanonicalAddr, ContractError> {
+        let croncat_factory = deps
+            .api
+            .addr_canonicalize(deps.api.addr_validate(addr)?.as_str())?;
+        for name in [TASK, MANAGER] {
+            let version =
+                self.query_latest_version_croncat_contract(deps, &croncat_factory, name)?;
+            self.query_contract_addr(deps, &croncat_factory, &version, name)?;
+        }
+        Ok(croncat_factory)
+    }
+
+    pub(crate) fn query_latest_version_croncat_contract(
+        &self,
+        deps: &Deps,
+        croncat_factory: &CanonicalAddr,
+        name: &str,
+    ) -> Result<[u8; 2], ContractError> {
+        let cc_factory = deps.api.addr_humanize(croncat_factory)?;
+
+        self.latest_versions
+            .query(&deps.querier, cc_factory, name)
+            .transpose()
+            .ok_or_else(|| ContractError::NoCronCatVersion {
+                name: name.to_string(),
+            })?
+            .map_err(|e| e.into())
+    }
+
+    /// Takes a CronCat contract name, queries the factory for the contract address of the version.
+    /// Returns a result with the addr, or an error.
+    pub(crate) fn query_contract_addr(
+        &self,
+        deps: &Deps,
+        croncat_factory: &CanonicalAddr,
+        version: &[u8; 2],
+        name: &str,
+    ) -> Result<Addr, ContractError> {
+        CONTRACT_ADDRS
+            .query(
+                &deps.querier,
+                deps.api.addr_humanize(croncat_factory)?,
+                (name, version),
+            )?
+            .ok_or_else(|| ContractError::NoCronCatContract {
+                name: name.to_string(),
+            })
+    }
+
+    /// `query_contract_addr` for the action's croncat factory and version,
+    /// resolved once per call with `cache`
+    fn cached_contract_addr(
+        &self,
+        deps: &Deps,
+        cache: &mut CroncatAddrs,
+        action: &CronKittyAction,
+        name: &'static str,
+    ) -> Result<Addr, ContractError> {
+        let key = (action.croncat_factory.to_vec(), action.version, name);
+        if let Some(addr) = cache.get(&key) {
+            return Ok(addr.clone());
+        }
+        let addr =
+            self.query_contract_addr(deps, &action.croncat_factory, &action.version, name)?;
+        cache.insert(key, addr.clone());
+        Ok(addr)
+    }
+}
+
+/// The action for croncat to call `ExecMsg::Execute` on this contract
+fn croncat_action(env: &Env, action_id: u64, gas_limit: Option<u64>) -> StdResult<Action> {
+    Ok(Action {
+        msg: CosmosMsg::<Empty>::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecMsg::Execute { action_id })?,
+            funds: vec![],
+        }),
+        gas_limit,
+    })
+}
+
+/// Native coins the proxy sends executing `msgs`,
+/// `None` if what a msg sends cannot be determined, e.g. custom, stargate or ibc msgs
+pub(crate) fn msgs_spend(msgs: &[CosmosMsg]) -> StdResult<Option<BTreeMap<String, Uint128>>> {
+    let mut coins: Vec<&Coin> = vec![];
+    for msg in msgs {
+        match msg {
+            CosmosMsg::Bank(BankMsg::Send { amount, .. })
+            | CosmosMsg::Bank(BankMsg::Burn { amount }) => coins.extend(amount),
+            CosmosMsg::Wasm(WasmMsg::Execute { funds, .. })
+            | CosmosMsg::Wasm(WasmMsg::Instantiate { funds, .. }) => coins.extend(funds),
+            CosmosMsg::Staking(StakingMsg::Delegate { amount, .. }) => coins.push(amount),
+            CosmosMsg::Wasm(WasmMsg::Migrate { .. })
+            | CosmosMsg::Wasm(WasmMsg::UpdateAdmin { .. })
+            | CosmosMsg::Wasm(WasmMsg::ClearAdmin { .. })
+            | CosmosMsg::Staking(StakingMsg::Undelegate { .. })
+            | CosmosMsg::Staking(StakingMsg::Redelegate { .. })
+            | CosmosMsg::Distribution(DistributionMsg::WithdrawDelegatorReward { .. })
+            | CosmosMsg::Gov(_) => {}
+            _ => return Ok(None),
+        }
+    }
+    sum_coins(coins.into_iter()).map(Some)
+}
+
+/// Checks `spend` of one execution against the caps of the action, given what it has `spent`
+pub(crate) fn check_spend_limit(
+    limit: &CronKittySpendLimit,
+    spend: &BTreeMap<String, Uint128>,
+    spent: &[Coin],
+) -> Result<(), ContractError> {
+    for (denom, amount) in spend {
+        if let Some(per_execution) = &limit.per_execution {
+            let max = amount_of(per_execution, denom);
+            if *amount > max {
+                return Err(ContractError::SpendLimitExceeded {
+                    denom: denom.clone(),
+                    limit: max,
+                    amount: *amount,
+                });
+            }
+        }
+        if let Some(total) = &limit.total {
+            let max = amount_of(total, denom);
+            let total_spend = amount_of(spent, denom)
+                .checked_add(*amount)
+                .map_err(StdError::from)?;
+            if total_spend > max {
+                return Err(ContractError::SpendLimitExceeded {
+                    denom: denom.clone(),
+                    limit: max,
+                    amount: total_spend,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks `spend` of one execution against the budget, given what was spent in the window
+pub(crate) fn check_budget(
+    budget: &CronKittyBudget,
+    spend: &BTreeMap<String, Uint128>,
+    spent: &[Coin],
+) -> Result<(), ContractError> {
+    for (denom, amount) in spend {
+        let max = amount_of(&budget.limit, denom);
+        let total_spend = amount_of(spent, denom)
+            .checked_add(*amount)
+            .map_err(StdError::from)?;
+        if total_spend > max {
+            return Err(ContractError::BudgetExceeded {
+                denom: denom.clone(),
+                limit: max,
+                amount: total_spend,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn amount_of(coins: &[Coin], denom: &str) -> Uint128 {
+    coins
+        .iter()
+        .filter(|c| c.denom == denom)
+        .map(|c| c.amount)
+        .sum()
+}
+
+/// Adds `spend` to `coins`
+fn add_coins(coins: &[Coin], spend: &BTreeMap<String, Uint128>) -> StdResult<Vec<Coin>> {
+    let mut totals = sum_coins(coins.iter())?;
+    for (denom, amount) in spend {
+        let total = totals.entry(denom.clone()).or_default();
+        *total = total.checked_add(*amount)?;
+    }
+    Ok(totals
+        .into_iter()
+        .map(|(denom, amount)| Coin { denom, amount })
+        .collect())
+}
+
+/// Sums up the amount of each denom
+fn sum_coins<'c>(coins: impl Iterator<Item = &'c Coin>) -> StdResult<BTreeMap<String, Uint128>> {
+    let mut totals: BTreeMap<String, Uint128> = BTreeMap::new();
+    for c in coins.filter(|c| !c.amount.is_zero()) {
+        let total = totals.entry(c.denom.clone()).or_default();
+        *total = total.checked_add(c.amount)?;
     }
+    Ok(totals)
 }
@@ -1,6 +1,6 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Coin, StdError, Uint128};
 use croncat_sdk_core::error::SdkError;
-use cw_utils::ParseReplyError;
+use cw_utils::{ParseReplyError, PaymentError};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -14,20 +14,26 @@ pub enum ContractError {
     #[error("{0}")]
     ParseReplyError(#[from] ParseReplyError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
     #[error("Unauthorized")]
     Unauthorized,
 
     #[error("Empty Funds")]
     EmptyFunds,
 
+    #[error("Funds sent do not match the funds of the tasks")]
+    FundsMismatch,
+
     #[error("Overflow")]
     Overflow,
 
-    #[error("InvalidReplyId")]
-    InvalidReplyId,
+    #[error("Unknown reply operation {tag}")]
+    UnknownReplyOp { tag: u64 },
 
-    #[error("Insufficient funds for Task")]
-    NotEnoughFundsForGas,
+    #[error("Insufficient funds for Task: required {required}, provided {provided}")]
+    NotEnoughFundsForGas { required: Coin, provided: Coin },
 
     #[error("Expected event not found")]
     ExpectedEventNotFound,
@@ -49,4 +55,63 @@ pub enum ContractError {
 
     #[error("Croncat task hash is not the one saved")]
     UnexpectedCroncatTaskHash,
+
+    #[error("Insufficient cw20 deposit of {token} for Task")]
+    InsufficientCw20Deposit { token: String },
+
+    #[error("Action is not active")]
+    ActionNotActive,
+
+    #[error("Action reached its failure limit and awaits removal")]
+    ActionFailed,
+
+    #[error("Action is not paused")]
+    ActionNotPaused,
+
+    #[error("Plugin has been torn down")]
+    Terminated,
+
+    #[error("Action is already on the latest croncat version")]
+    ActionOnLatestVersion,
+
+    #[error("Tasks with a cw20 balance cannot be moved to another croncat manager")]
+    Cw20TaskNotMigratable,
+
+    #[error("Invalid cron schedule {0}")]
+    InvalidCron(String),
+
+    #[error("Max consecutive failures must be greater than 0")]
+    InvalidFailureLimit,
+
+    #[error("Budget window must be greater than 0")]
+    InvalidBudget,
+
+    #[error("Budget of {limit}{denom} exceeded with {amount}{denom}")]
+    BudgetExceeded {
+        denom: String,
+        limit: Uint128,
+        amount: Uint128,
+    },
+
+    #[error("Cannot determine the coins sent by the msgs to check the spend limit or budget")]
+    UnmeteredSpend,
+
+    #[error("Cannot determine the coins sent by the msgs of actions {action_ids:?}")]
+    UnmeteredActions { action_ids: Vec<u64> },
+
+    #[error("Spend limit of {limit}{denom} exceeded with {amount}{denom}")]
+    SpendLimitExceeded {
+        denom: String,
+        limit: Uint128,
+        amount: Uint128,
+    },
+
+    #[error("Cannot migrate from {contract} {version}")]
+    InvalidMigration { contract: String, version: String },
+
+    #[error("Cannot migrate from {from} to older version {to}")]
+    MigrationDowngrade { from: String, to: String },
+
+    #[error("Invalid version {0}")]
+    InvalidVersion(String),
 }
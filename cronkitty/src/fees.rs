@@ -0,0 +1,65 @@
+use crate::error::ContractError;
+use cosmwasm_std::Uint128;
+use croncat_sdk_manager::types::Config as CCManagerConfig;
+use croncat_sdk_tasks::types::{Action, Config as CCTasksConfig, Interval, TaskRequest};
+
+/// Sums up the gas of all the actions, as croncat only calls the one action on this contract
+pub fn collapse_gas_limit(actions: &[Action]) -> Result<Option<u64>, ContractError> {
+    let gas_limit = actions.iter().try_fold(0u64, |acc, a| {
+        acc.checked_add(a.gas_limit.unwrap_or(0))
+            .ok_or(ContractError::Overflow)
+    })?;
+
+    if gas_limit == 0 {
+        Ok(None)
+    } else {
+        Ok(Some(gas_limit))
+    }
+}
+
+/// The gas croncat accounts for one execution of the collapsed task:
+/// gas_base_fee + gas_action_fee + gas_limit, and gas_query_fee for each query
+pub fn task_gas(task: &TaskRequest, config: &CCTasksConfig) -> Result<u64, ContractError> {
+    let gas_limit = collapse_gas_limit(&task.actions)?.unwrap_or(0);
+    let queries = task.queries.as_ref().map(|q| q.len() as u64).unwrap_or(0);
+    let query_gas = config
+        .gas_query_fee
+        .checked_mul(queries)
+        .ok_or(ContractError::Overflow)?;
+
+    [
+        config.gas_base_fee,
+        config.gas_action_fee,
+        gas_limit,
+        query_gas,
+    ]
+    .iter()
+    .try_fold(0u64, |acc, g| acc.checked_add(*g))
+    .ok_or(ContractError::Overflow)
+}
+
+/// The native amount the croncat manager requires for one execution:
+/// the task gas plus treasury_fee and agent_fee at the manager's gas price
+pub fn native_cost_per_execution(
+    gas: u64,
+    config: &CCManagerConfig,
+) -> Result<Uint128, ContractError> {
+    let gas = gas
+        .checked_add(config.agent_fee as u64)
+        .and_then(|g| g.checked_add(config.treasury_fee as u64))
+        .ok_or(ContractError::Overflow)?;
+    Ok(config.gas_price.calculate(gas)?.into())
+}
+
+/// If it is not a one-off task, croncat requires the fees for two executions
+pub fn min_deposit(
+    cost_per_execution: Uint128,
+    interval: &Interval,
+) -> Result<Uint128, ContractError> {
+    match interval {
+        Interval::Once => Ok(cost_per_execution),
+        _ => cost_per_execution
+            .checked_mul(Uint128::new(2))
+            .map_err(|_| ContractError::Overflow),
+    }
+}
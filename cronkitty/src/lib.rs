@@ -1,5 +1,9 @@
 pub mod contract;
 pub mod error;
+mod fees;
+mod migrations;
+mod reply_id;
+mod schedule;
 
 #[cfg(test)]
 pub mod multitest;
@@ -9,13 +13,16 @@ mod tests;
 mod entry_points {
     use cosmwasm_std::{
         entry_point, from_binary, Binary, Deps, DepsMut, Env, Event, MessageInfo, Reply, Response,
+        SubMsgResult,
     };
     use cw_utils::parse_reply_execute_data;
 
     use crate::contract::{
-        ContractExecMsg, ContractQueryMsg, CronKittyPlugin, CronkittyActionRef, InstantiateMsg,
+        ActionStatus, ContractExecMsg, ContractQueryMsg, CronKittyPlugin, InstantiateMsg,
+        MigrateMsg, MANAGER,
     };
     use crate::error::ContractError;
+    use crate::reply_id::{parse_reply_id, ReplyOp};
     use croncat_sdk_tasks::types::TaskExecutionInfo;
 
     const CONTRACT: CronKittyPlugin = CronKittyPlugin::new();
@@ -46,47 +53,87 @@ mod entry_points {
     }
 
     #[entry_point]
-    pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, ContractError> {
-        if let (_, _, Some(task_hash)) = CONTRACT.actions.load(deps.storage, reply.id)? {
-            // This means task_hash was stored, i.e. replied from remove_task
-            CONTRACT.actions.remove(deps.storage, reply.id);
-            Ok(Response::new().add_event(
-                Event::new("vectis.cronkitty.v1.ReplyRemoveTask")
-                    .add_attribute("Task ID", reply.id.to_string())
-                    .add_attribute("Task Hash", task_hash),
-            ))
-        } else {
-            let expected_id = CONTRACT.next_action_id.load(deps.storage)?;
-            let reply_id = reply.id;
-            if reply_id == expected_id {
-                let reply_data = parse_reply_execute_data(reply)?
-                    .data
-                    .ok_or(ContractError::UnexpectedCroncatTaskReply)?;
-                let task_exec_info: TaskExecutionInfo = from_binary(&reply_data)?;
-                let task_hash = task_exec_info.task_hash;
-                CONTRACT.actions.update(
-                    deps.storage,
-                    expected_id,
-                    |t| -> Result<CronkittyActionRef, ContractError> {
-                        let mut task = t.ok_or(ContractError::TaskNotFound)?;
-                        task.2 = Some(task_hash.clone());
-                        Ok(task)
-                    },
-                )?;
+    pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+        msg.dispatch(&CONTRACT, (deps, env))
+    }
 
-                CONTRACT.next_action_id.update(deps.storage, |id| {
-                    id.checked_add(1).ok_or(ContractError::Overflow)
-                })?;
+    #[entry_point]
+    pub fn reply(deps: DepsMut, env: Env, reply: Reply) -> Result<Response, ContractError> {
+        match parse_reply_id(reply.id)? {
+            (ReplyOp::CreateTask, action_id) => reply_create_task(deps, action_id, reply),
+            (ReplyOp::RemoveTask, action_id) => reply_remove_task(deps, env, action_id),
+            (ReplyOp::ProxyExecute, action_id) => reply_proxy_execute(deps, env, action_id, reply),
+        }
+    }
+
+    /// Records the outcome of the proxy executing the msgs of the action,
+    /// a failure is reported in the event without failing the croncat execution
+    fn reply_proxy_execute(
+        deps: DepsMut,
+        env: Env,
+        action_id: u64,
+        reply: Reply,
+    ) -> Result<Response, ContractError> {
+        match reply.result {
+            SubMsgResult::Ok(_) => CONTRACT.execution_succeeded(deps, &env, action_id),
+            SubMsgResult::Err(error) => CONTRACT.execution_failed(deps, action_id, error),
+        }
+    }
 
-                Ok(Response::new().add_event(
-                    Event::new("vectis.cronkitty.v1.ReplyCreateTask")
-                        .add_attribute("Task ID", reply_id.to_string())
-                        .add_attribute("Task Hash", task_hash),
-                ))
-            } else {
-                Err(ContractError::InvalidReplyId)
-            }
+    fn reply_create_task(
+        deps: DepsMut,
+        action_id: u64,
+        reply: Reply,
+    ) -> Result<Response, ContractError> {
+        // The action is saved when creating the submsg, including recreating by update_task
+        let mut action = CONTRACT.actions.load(deps.storage, action_id)?;
+        let reply_data = parse_reply_execute_data(reply)?
+            .data
+            .ok_or(ContractError::UnexpectedCroncatTaskReply)?;
+        let task_exec_info: TaskExecutionInfo = from_binary(&reply_data)?;
+        let task_hash = task_exec_info.task_hash;
+        action.task_hash = Some(task_hash.clone());
+        if action.status == ActionStatus::Pending {
+            action.status = ActionStatus::Active;
         }
+        CONTRACT.save_action(deps.storage, action_id, &action)?;
+        CONTRACT
+            .task_hashes
+            .save(deps.storage, &task_hash, &action_id)?;
+
+        Ok(Response::new().add_event(
+            Event::new("vectis.cronkitty.v1.ReplyCreateTask")
+                .add_attribute("Task ID", action_id.to_string())
+                .add_attribute("Task Hash", task_hash),
+        ))
+    }
+
+    fn reply_remove_task(
+        deps: DepsMut,
+        env: Env,
+        action_id: u64,
+    ) -> Result<Response, ContractError> {
+        let action = CONTRACT.actions.load(deps.storage, action_id)?;
+        let task_hash = action.task_hash.ok_or(ContractError::TaskHashNotFound)?;
+        let manager = CONTRACT.query_contract_addr(
+            &deps.as_ref(),
+            &action.croncat_factory,
+            &action.version,
+            MANAGER,
+        )?;
+        CONTRACT.remove_action(deps.storage, action_id)?;
+        CONTRACT.task_hashes.remove(deps.storage, &task_hash);
+
+        // croncat has refunded the task balance to this contract,
+        // cw20 to its temp balance on the manager
+        let res = CONTRACT.refund_owner(deps.as_ref(), &env, None)?;
+        Ok(CONTRACT
+            .withdraw_cw20(deps, &env, &manager, res)?
+            .add_event(
+                Event::new("vectis.cronkitty.v1.ReplyRemoveTask")
+                    .add_attribute("Task ID", action_id.to_string())
+                    .add_attribute("Task Hash", task_hash),
+            ))
     }
 }
 
@@ -0,0 +1,102 @@
+use crate::{
+    contract::{
+        ActionStatus, CronKittyAction, CronKittyPlugin, CronKittySchedule, LEGACY_ACTIONS, TASK,
+    },
+    error::ContractError,
+};
+use cosmwasm_std::{DepsMut, Env, Order, StdResult};
+use croncat_sdk_tasks::{msg::TasksQueryMsg, types::TaskResponse};
+use semver::Version;
+
+pub type StateTransform = fn(DepsMut, &Env) -> Result<(), ContractError>;
+
+/// State transforms keyed by the version that introduced them.
+/// On migrate, every transform newer than the stored cw2 version runs in this order.
+pub const STATE_TRANSFORMS: &[(&str, StateTransform)] = &[("0.3.0", actions_to_record)];
+
+pub fn parse_version(version: &str) -> Result<Version, ContractError> {
+    version
+        .parse()
+        .map_err(|_| ContractError::InvalidVersion(version.to_string()))
+}
+
+/// Rewrites the 0.2.x tuple entries into `CronKittyAction` and indexes them by task hash,
+/// the actions were created on the currently stored croncat factory.
+/// The schedule of each task is read from croncat, actions whose task is gone are `Ended`.
+fn actions_to_record(deps: DepsMut, _env: &Env) -> Result<(), ContractError> {
+    let plugin = CronKittyPlugin::new();
+    let CronKittyPlugin {
+        actions,
+        task_hashes,
+        croncat_factory,
+        ..
+    } = &plugin;
+    let croncat_factory = croncat_factory.load(deps.storage)?;
+    let legacy = LEGACY_ACTIONS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (id, (version, msgs, task_hash)) in legacy {
+        // The croncat version may be gone from the factory, along with the task
+        let task_addr = plugin
+            .query_contract_addr(&deps.as_ref(), &croncat_factory, &version, TASK)
+            .ok();
+        let croncat_task = match (&task_addr, &task_hash) {
+            (Some(task_addr), Some(task_hash)) => {
+                deps.querier
+                    .query_wasm_smart::<TaskResponse>(
+                        task_addr,
+                        &TasksQueryMsg::Task {
+                            task_hash: task_hash.clone(),
+                        },
+                    )?
+                    .task
+            }
+            _ => None,
+        };
+        let (status, task_hash, schedule) = match (task_hash, croncat_task) {
+            (Some(task_hash), Some(croncat_task)) => {
+                task_hashes.save(deps.storage, &task_hash, &id)?;
+                (
+                    ActionStatus::Active,
+                    Some(task_hash),
+                    Some(CronKittySchedule::from_task_info(croncat_task)?),
+                )
+            }
+            // As `sync_actions` does for a task croncat has removed
+            (Some(_), None) => (ActionStatus::Ended, None, None),
+            (None, _) => (ActionStatus::Pending, None, None),
+        };
+        // The queries of an action resolve its croncat contracts,
+        // an action on a version gone from the factory is moved to the latest one
+        let version = match task_addr {
+            Some(_) => version,
+            None => plugin.query_latest_version_croncat_contract(
+                &deps.as_ref(),
+                &croncat_factory,
+                TASK,
+            )?,
+        };
+        // The legacy entry shares the `actions` namespace, count the action as new
+        plugin.update_status_counts(deps.storage, None, Some(&status))?;
+        actions.save(
+            deps.storage,
+            id,
+            &CronKittyAction {
+                version,
+                croncat_factory: croncat_factory.clone(),
+                msgs,
+                task_hash,
+                created_at: None,
+                status,
+                label: None,
+                schedule,
+                failure_limit: None,
+                consecutive_failures: 0,
+                spend_limit: None,
+                spent: vec![],
+            },
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use crate::error::ContractError;
+
+/// Submessage reply ids carry the operation in the top byte and the action id in the rest
+const OP_SHIFT: u32 = 56;
+pub(crate) const ACTION_ID_MASK: u64 = (1 << OP_SHIFT) - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyOp {
+    CreateTask = 1,
+    RemoveTask = 2,
+    ProxyExecute = 3,
+}
+
+impl ReplyOp {
+    /// Every operation, a reply id is parsed by matching its tag against these
+    pub const ALL: [ReplyOp; 3] = [
+        ReplyOp::CreateTask,
+        ReplyOp::RemoveTask,
+        ReplyOp::ProxyExecute,
+    ];
+
+    /// The tag of this operation in the reply id
+    pub fn tag(self) -> u64 {
+        self as u64
+    }
+
+    /// The reply id of this operation on `action_id`
+    pub fn reply_id(self, action_id: u64) -> Result<u64, ContractError> {
+        if action_id > ACTION_ID_MASK {
+            return Err(ContractError::Overflow);
+        }
+        Ok(self.tag() << OP_SHIFT | action_id)
+    }
+}
+
+/// Returns the operation and the action id of a reply id
+pub fn parse_reply_id(id: u64) -> Result<(ReplyOp, u64), ContractError> {
+    let tag = id >> OP_SHIFT;
+    let op = ReplyOp::ALL
+        .into_iter()
+        .find(|op| op.tag() == tag)
+        .ok_or(ContractError::UnknownReplyOp { tag })?;
+    Ok((op, id & ACTION_ID_MASK))
+}
@@ -0,0 +1,82 @@
+use crate::{
+    contract::{CronKittyExecutionSlot, CronKittySchedule},
+    error::ContractError,
+};
+use cosmwasm_std::{BlockInfo, Timestamp};
+use cron_schedule::Schedule;
+use croncat_sdk_tasks::types::{Boundary, Interval};
+use std::str::FromStr;
+
+/// Predicts the next `count` executions of a task after the current block.
+/// `executed` is whether croncat has executed the task, which ends `Interval::Once`.
+///
+/// Block based intervals only take a height boundary into account and
+/// `Interval::Cron` only a time boundary, as croncat does not know the other in advance.
+pub fn next_executions(
+    schedule: &CronKittySchedule,
+    block: &BlockInfo,
+    executed: bool,
+    count: usize,
+) -> Result<Vec<CronKittyExecutionSlot>, ContractError> {
+    let boundary = schedule.boundary.as_ref();
+    match &schedule.interval {
+        Interval::Once if executed => Ok(vec![]),
+        Interval::Once => Ok(next_heights(boundary, block.height, 1, count.min(1))),
+        Interval::Immediate => Ok(next_heights(boundary, block.height, 1, count)),
+        Interval::Block(every) => Ok(next_heights(boundary, block.height, *every, count)),
+        Interval::Cron(crontab) => next_times(boundary, block.time, crontab, count),
+    }
+}
+
+/// Croncat runs block intervals at the multiples of `every` after the current height
+fn next_heights(
+    boundary: Option<&Boundary>,
+    height: u64,
+    every: u64,
+    count: usize,
+) -> Vec<CronKittyExecutionSlot> {
+    let (start, end) = match boundary {
+        Some(Boundary::Height(b)) => (b.start.map(|s| s.u64()), b.end.map(|e| e.u64())),
+        _ => (None, None),
+    };
+    let every = every.max(1);
+    let after = start.map_or(height, |s| height.max(s.saturating_sub(1)));
+    let first = (after / every)
+        .checked_add(1)
+        .and_then(|n| n.checked_mul(every));
+
+    std::iter::successors(first, |h| h.checked_add(every))
+        .take_while(|h| end.map_or(true, |end| *h <= end))
+        .take(count)
+        .map(CronKittyExecutionSlot::Block)
+        .collect()
+}
+
+fn next_times(
+    boundary: Option<&Boundary>,
+    time: Timestamp,
+    crontab: &str,
+    count: usize,
+) -> Result<Vec<CronKittyExecutionSlot>, ContractError> {
+    let (start, end) = match boundary {
+        Some(Boundary::Time(b)) => (b.start, b.end),
+        _ => (None, None),
+    };
+    let schedule =
+        Schedule::from_str(crontab).map_err(|_| ContractError::InvalidCron(crontab.to_string()))?;
+
+    let mut after = start.map_or(time.nanos(), |s| {
+        time.nanos().max(s.nanos().saturating_sub(1))
+    });
+    let mut slots = vec![];
+    while slots.len() < count {
+        match schedule.next_after(&after) {
+            Some(next) if end.map_or(true, |end| next <= end.nanos()) => {
+                slots.push(CronKittyExecutionSlot::Time(Timestamp::from_nanos(next)));
+                after = next;
+            }
+            _ => break,
+        }
+    }
+    Ok(slots)
+}
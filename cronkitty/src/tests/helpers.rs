@@ -1,16 +1,22 @@
 pub use crate::contract::{
-    CronKittyActionResp, CronKittyPlugin, ExecMsg as CronKittyExecMsg,
-    InstantiateMsg as CronKittyInstMsg, QueryMsg as CronKittyQueryMsg,
+    CronKittyActionResp, CronKittyPlugin, CronKittyTaskCostResp, CronkittyActionRef,
+    ExecMsg as CronKittyExecMsg, InstantiateMsg as CronKittyInstMsg,
+    MigrateMsg as CronKittyMigrateMsg, QueryMsg as CronKittyQueryMsg,
+};
+use crate::{contract::LEGACY_ACTIONS, tests::croncat_helpers::*};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, BankMsg, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult,
+    Uint128,
 };
-use crate::tests::croncat_helpers::*;
-use cosmwasm_std::{Addr, BankMsg, Empty};
 use croncat_sdk_agents::msg::ExecuteMsg as AgentExecuteMsg;
 pub use croncat_sdk_core::types::GasPrice;
 use croncat_sdk_tasks::{
     msg::TasksQueryMsg,
     types::{Action, Interval, TaskInfo, TaskRequest},
 };
-use cw_multi_test::{App, Executor};
+use cw20::Cw20Coin;
+use cw_multi_test::{App, Contract, ContractWrapper, Executor};
 use vectis_contract_tests::common::common::*;
 use vectis_contract_tests::common::{
     base_common::HubChainSuite,
@@ -28,6 +34,16 @@ pub fn setup_croncat_contracts(
     app: &mut App,
     deployer_signer: &Addr,
     controller: &Addr,
+) -> CronCatContracts {
+    setup_croncat_contracts_with_cw20(app, deployer_signer, controller, vec![])
+}
+
+/// Croncat contracts with the manager accepting `cw20_whitelist` tokens
+pub fn setup_croncat_contracts_with_cw20(
+    app: &mut App,
+    deployer_signer: &Addr,
+    controller: &Addr,
+    cw20_whitelist: Vec<String>,
 ) -> CronCatContracts {
     app.send_tokens(
         controller.clone(),
@@ -40,8 +56,11 @@ pub fn setup_croncat_contracts(
     // ==============================================================
     let factory_addr = init_factory(app);
 
-    let manager_instantiate_msg: croncat_sdk_manager::msg::ManagerInstantiateMsg =
+    let mut manager_instantiate_msg: croncat_sdk_manager::msg::ManagerInstantiateMsg =
         default_manager_instantiate_message();
+    if !cw20_whitelist.is_empty() {
+        manager_instantiate_msg.cw20_whitelist = Some(cw20_whitelist);
+    }
     let manager = init_manager(
         app,
         &manager_instantiate_msg,
@@ -81,6 +100,38 @@ pub fn setup_croncat_contracts(
     }
 }
 
+pub fn cw20_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    ))
+}
+
+/// Instantiates a cw20 token with `amount` held by `holder`
+pub fn init_cw20(app: &mut App, holder: &Addr, amount: u128) -> Addr {
+    let code_id = app.store_code(cw20_contract());
+    app.instantiate_contract(
+        code_id,
+        holder.clone(),
+        &cw20_base::msg::InstantiateMsg {
+            name: "Cronkitty Token".into(),
+            symbol: "CKT".into(),
+            decimals: 6,
+            initial_balances: vec![Cw20Coin {
+                address: holder.to_string(),
+                amount: Uint128::from(amount),
+            }],
+            mint: None,
+            marketing: None,
+        },
+        &[],
+        "cw20",
+        None,
+    )
+    .unwrap()
+}
+
 pub fn register_cronkitty(suite: &mut HubChainSuite, registry_fee: u128) {
     // ==============================================================
     // Upload Cronkitty and add to registry by the pluginCommittee
@@ -152,16 +203,9 @@ pub fn set_up_proxy_and_install_cronkitty(
     )
 }
 
-pub fn create_task(
-    suite: &mut HubChainSuite,
-    proxy: &Addr,
-    cronkitty: &Addr,
-    gas_limit: u64,
-    fund: Coin,
-    call_back_msg: CosmosMsg,
-    tasks_addr: &Addr,
-) -> Vec<TaskInfo> {
-    let task = TaskRequest {
+/// A task to execute `call_back_msg` every 5 blocks
+pub fn task_request(gas_limit: u64, call_back_msg: CosmosMsg) -> TaskRequest {
+    TaskRequest {
         interval: Interval::Block(5),
         boundary: None,
         stop_on_fail: false,
@@ -172,7 +216,40 @@ pub fn create_task(
         queries: None,
         transforms: None,
         cw20: None,
-    };
+    }
+}
+
+/// The minimum deposit to create `task_request(gas_limit, call_back_msg)`
+pub fn required_deposit(
+    suite: &HubChainSuite,
+    cronkitty: &Addr,
+    gas_limit: u64,
+    call_back_msg: CosmosMsg,
+) -> Coin {
+    let estimate: CronKittyTaskCostResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            cronkitty,
+            &CronKittyQueryMsg::EstimateTaskCost {
+                task: task_request(gas_limit, call_back_msg),
+                executions: None,
+            },
+        )
+        .unwrap();
+    estimate.min_deposit
+}
+
+pub fn create_task(
+    suite: &mut HubChainSuite,
+    proxy: &Addr,
+    cronkitty: &Addr,
+    gas_limit: u64,
+    fund: Coin,
+    call_back_msg: CosmosMsg,
+    tasks_addr: &Addr,
+) -> Vec<TaskInfo> {
+    let task = task_request(gas_limit, call_back_msg);
 
     suite
         .app
@@ -181,7 +258,7 @@ pub fn create_task(
             proxy.clone(),
             &proxy_exec(
                 &cronkitty,
-                &CronKittyExecMsg::CreateTask { task },
+                &CronKittyExecMsg::CreateTask { task, label: None },
                 vec![fund],
             ),
             &[],
@@ -216,23 +293,76 @@ pub fn mock_setup_a_task(
         100_000,
     );
     let gas_limit = 150_000u64;
-    let gas_price_on_mgr = GasPrice::default();
-    let required = gas_price_on_mgr
-        .calculate(gas_limit + AGENT_FEE + TREASURY_FEE + GAS_BASE_FEE + GAS_ACTION_FEE)
-        .unwrap()
-        * 2;
     let msg = CosmosMsg::Bank(BankMsg::Burn {
         amount: vec![coin(1, DENOM)],
     });
+    let required = required_deposit(suite, &cronkitty, gas_limit, msg.clone());
 
     let tasks_on_croncat = create_task(
         suite,
         &proxy,
         &cronkitty,
         gas_limit,
-        coin(required, DENOM),
+        required,
         msg.clone(),
         &cc_contracts.tasks_addr,
     );
     (tasks_on_croncat[0].clone(), proxy, cronkitty)
 }
+
+/// Instantiates cronkitty state in the 0.2.x layout
+#[cw_serde]
+pub struct LegacyCronKittyInstMsg {
+    /// cw2 contract name and version
+    pub contract_version: (String, String),
+    pub croncat_factory_addr: String,
+    pub vectis_account_addr: String,
+    pub actions: Vec<(u64, CronkittyActionRef)>,
+}
+
+fn legacy_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: LegacyCronKittyInstMsg,
+) -> StdResult<Response> {
+    let plugin = CronKittyPlugin::new();
+    let (contract, version) = msg.contract_version;
+    cw2::set_contract_version(deps.storage, contract, version)?;
+    plugin.owner.save(
+        deps.storage,
+        &deps.api.addr_canonicalize(&msg.vectis_account_addr)?,
+    )?;
+    plugin.croncat_factory.save(
+        deps.storage,
+        &deps.api.addr_canonicalize(&msg.croncat_factory_addr)?,
+    )?;
+    plugin
+        .next_action_id
+        .save(deps.storage, &(msg.actions.len() as u64))?;
+    for (id, action) in msg.actions {
+        LEGACY_ACTIONS.save(deps.storage, id, &action)?;
+    }
+    Ok(Response::new())
+}
+
+fn legacy_execute(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Err(StdError::generic_err("legacy cronkitty"))
+}
+
+fn legacy_query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    Err(StdError::generic_err("legacy cronkitty"))
+}
+
+pub fn legacy_cronkitty_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        legacy_execute,
+        legacy_instantiate,
+        legacy_query,
+    ))
+}
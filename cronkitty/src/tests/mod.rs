@@ -1,3 +1,6 @@
 pub mod croncat_helpers;
 pub mod helpers;
+mod reply_id;
+mod schedule;
+mod spend;
 pub mod tests;
@@ -0,0 +1,47 @@
+use crate::error::ContractError;
+use crate::reply_id::{parse_reply_id, ReplyOp, ACTION_ID_MASK};
+
+#[test]
+fn reply_id_round_trips() {
+    for op in ReplyOp::ALL {
+        for action_id in [0, 1, 42, ACTION_ID_MASK] {
+            let id = op.reply_id(action_id).unwrap();
+            assert_eq!(parse_reply_id(id).unwrap(), (op, action_id));
+        }
+    }
+}
+
+#[test]
+fn reply_ids_of_operations_differ() {
+    let ids: Vec<u64> = ReplyOp::ALL
+        .iter()
+        .map(|op| op.reply_id(7).unwrap())
+        .collect();
+    for (i, id) in ids.iter().enumerate() {
+        assert!(!ids[i + 1..].contains(id));
+    }
+}
+
+#[test]
+fn action_id_overflowing_the_mask_is_rejected() {
+    for op in ReplyOp::ALL {
+        assert_eq!(
+            op.reply_id(ACTION_ID_MASK + 1).unwrap_err(),
+            ContractError::Overflow
+        );
+        assert_eq!(op.reply_id(u64::MAX).unwrap_err(), ContractError::Overflow);
+    }
+}
+
+#[test]
+fn unknown_reply_op_is_rejected() {
+    // action ids without a tag, e.g. reply ids from before the tags
+    assert_eq!(
+        parse_reply_id(5).unwrap_err(),
+        ContractError::UnknownReplyOp { tag: 0 }
+    );
+    assert_eq!(
+        parse_reply_id(0xff << 56 | 5).unwrap_err(),
+        ContractError::UnknownReplyOp { tag: 0xff }
+    );
+}
@@ -0,0 +1,143 @@
+use crate::contract::{CronKittyExecutionSlot, CronKittySchedule};
+use crate::schedule::next_executions;
+use cosmwasm_std::{BlockInfo, Timestamp, Uint64};
+use croncat_sdk_tasks::types::{Boundary, BoundaryHeight, BoundaryTime, Interval};
+
+const HEIGHT: u64 = 100;
+const HOUR: u64 = 3600;
+// 1000 hours and a half after the epoch
+const TIME: u64 = 1000 * HOUR + HOUR / 2;
+
+fn block() -> BlockInfo {
+    BlockInfo {
+        height: HEIGHT,
+        time: Timestamp::from_seconds(TIME),
+        chain_id: "chain".to_string(),
+    }
+}
+
+fn schedule(interval: Interval, boundary: Option<Boundary>) -> CronKittySchedule {
+    CronKittySchedule {
+        interval,
+        boundary,
+        stop_on_fail: false,
+        queries: None,
+        transforms: None,
+        gas_limit: None,
+    }
+}
+
+fn height_boundary(start: Option<u64>, end: Option<u64>) -> Option<Boundary> {
+    Some(Boundary::Height(BoundaryHeight {
+        start: start.map(Uint64::new),
+        end: end.map(Uint64::new),
+    }))
+}
+
+fn blocks(heights: &[u64]) -> Vec<CronKittyExecutionSlot> {
+    heights
+        .iter()
+        .map(|h| CronKittyExecutionSlot::Block(*h))
+        .collect()
+}
+
+#[test]
+fn once_runs_on_the_next_block_until_executed() {
+    let once = schedule(Interval::Once, None);
+    assert_eq!(
+        next_executions(&once, &block(), false, 3).unwrap(),
+        blocks(&[HEIGHT + 1])
+    );
+    assert!(next_executions(&once, &block(), true, 3)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn immediate_runs_on_every_block() {
+    let immediate = schedule(Interval::Immediate, None);
+    assert_eq!(
+        next_executions(&immediate, &block(), true, 3).unwrap(),
+        blocks(&[HEIGHT + 1, HEIGHT + 2, HEIGHT + 3])
+    );
+}
+
+#[test]
+fn block_interval_starts_at_the_multiples_after_the_boundary_start() {
+    // the start is a multiple of `every` and included
+    let aligned = schedule(Interval::Block(10), height_boundary(Some(150), None));
+    assert_eq!(
+        next_executions(&aligned, &block(), false, 2).unwrap(),
+        blocks(&[150, 160])
+    );
+
+    // the start is not a multiple of `every`
+    let unaligned = schedule(Interval::Block(10), height_boundary(Some(155), None));
+    assert_eq!(
+        next_executions(&unaligned, &block(), false, 2).unwrap(),
+        blocks(&[160, 170])
+    );
+
+    // a past start does not change the executions
+    let past = schedule(Interval::Block(10), height_boundary(Some(50), None));
+    assert_eq!(
+        next_executions(&past, &block(), false, 2).unwrap(),
+        blocks(&[110, 120])
+    );
+}
+
+#[test]
+fn block_interval_stops_at_the_boundary_end() {
+    let ending = schedule(Interval::Block(10), height_boundary(None, Some(125)));
+    assert_eq!(
+        next_executions(&ending, &block(), false, 5).unwrap(),
+        blocks(&[110, 120])
+    );
+
+    // the end is included
+    let ending = schedule(Interval::Block(10), height_boundary(None, Some(120)));
+    assert_eq!(
+        next_executions(&ending, &block(), false, 5).unwrap(),
+        blocks(&[110, 120])
+    );
+}
+
+#[test]
+fn cron_runs_within_the_time_boundary() {
+    // every hour at minute 0
+    let hourly = "0 0 * * * *".to_string();
+    let slots = |hours: &[u64]| -> Vec<CronKittyExecutionSlot> {
+        hours
+            .iter()
+            .map(|h| CronKittyExecutionSlot::Time(Timestamp::from_seconds(h * HOUR)))
+            .collect()
+    };
+
+    let unbounded = schedule(Interval::Cron(hourly.clone()), None);
+    assert_eq!(
+        next_executions(&unbounded, &block(), false, 2).unwrap(),
+        slots(&[1001, 1002])
+    );
+
+    let bounded = schedule(
+        Interval::Cron(hourly),
+        Some(Boundary::Time(BoundaryTime {
+            start: Some(Timestamp::from_seconds(1003 * HOUR)),
+            end: Some(Timestamp::from_seconds(1004 * HOUR)),
+        })),
+    );
+    assert_eq!(
+        next_executions(&bounded, &block(), false, 5).unwrap(),
+        slots(&[1003, 1004])
+    );
+
+    // a height boundary is not known for cron intervals
+    let height_bounded = schedule(
+        Interval::Cron("0 0 * * * *".to_string()),
+        height_boundary(Some(1000), Some(1001)),
+    );
+    assert_eq!(
+        next_executions(&height_bounded, &block(), false, 1).unwrap(),
+        slots(&[1001])
+    );
+}
@@ -0,0 +1,125 @@
+use crate::contract::{
+    check_budget, check_spend_limit, msgs_spend, CronKittyBudget, CronKittySpendLimit,
+};
+use crate::error::ContractError;
+use cosmwasm_std::{coin, BankMsg, CosmosMsg, Empty, StakingMsg, Uint128, WasmMsg};
+
+const DENOM: &str = "ucosm";
+
+#[test]
+fn msgs_spend_sums_up_the_coins_sent() {
+    let msgs = vec![
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "to".into(),
+            amount: vec![coin(10, DENOM), coin(1, "other")],
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "contract".into(),
+            msg: Default::default(),
+            funds: vec![coin(5, DENOM)],
+        }),
+        CosmosMsg::Staking(StakingMsg::Delegate {
+            validator: "validator".into(),
+            amount: coin(2, DENOM),
+        }),
+        CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: "validator".into(),
+            amount: coin(100, DENOM),
+        }),
+    ];
+    let spend = msgs_spend(&msgs).unwrap().unwrap();
+    assert_eq!(spend.len(), 2);
+    assert_eq!(spend[DENOM], Uint128::new(17));
+    assert_eq!(spend["other"], Uint128::new(1));
+}
+
+#[test]
+fn msgs_spend_of_unmetered_msgs_is_unknown() {
+    let msgs = vec![
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "to".into(),
+            amount: vec![coin(10, DENOM)],
+        }),
+        CosmosMsg::Custom(Empty {}),
+    ];
+    assert_eq!(msgs_spend(&msgs).unwrap(), None);
+}
+
+#[test]
+fn spend_limit_is_checked_per_execution_and_in_total() {
+    let spend = msgs_spend(&[CosmosMsg::Bank(BankMsg::Send {
+        to_address: "to".into(),
+        amount: vec![coin(10, DENOM)],
+    })])
+    .unwrap()
+    .unwrap();
+
+    let limit = CronKittySpendLimit {
+        per_execution: Some(vec![coin(5, DENOM)]),
+        total: None,
+    };
+    assert_eq!(
+        check_spend_limit(&limit, &spend, &[]).unwrap_err(),
+        ContractError::SpendLimitExceeded {
+            denom: DENOM.into(),
+            limit: Uint128::new(5),
+            amount: Uint128::new(10),
+        }
+    );
+
+    let limit = CronKittySpendLimit {
+        per_execution: Some(vec![coin(10, DENOM)]),
+        total: Some(vec![coin(15, DENOM)]),
+    };
+    check_spend_limit(&limit, &spend, &[]).unwrap();
+    assert_eq!(
+        check_spend_limit(&limit, &spend, &[coin(10, DENOM)]).unwrap_err(),
+        ContractError::SpendLimitExceeded {
+            denom: DENOM.into(),
+            limit: Uint128::new(15),
+            amount: Uint128::new(20),
+        }
+    );
+
+    // denoms not in the caps cannot be sent
+    assert_eq!(
+        check_spend_limit(
+            &CronKittySpendLimit {
+                per_execution: Some(vec![coin(10, "other")]),
+                total: None,
+            },
+            &spend,
+            &[]
+        )
+        .unwrap_err(),
+        ContractError::SpendLimitExceeded {
+            denom: DENOM.into(),
+            limit: Uint128::zero(),
+            amount: Uint128::new(10),
+        }
+    );
+}
+
+#[test]
+fn budget_is_checked_against_the_spent_in_the_window() {
+    let spend = msgs_spend(&[CosmosMsg::Bank(BankMsg::Send {
+        to_address: "to".into(),
+        amount: vec![coin(10, DENOM)],
+    })])
+    .unwrap()
+    .unwrap();
+    let budget = CronKittyBudget {
+        limit: vec![coin(20, DENOM)],
+        window: 100,
+    };
+
+    check_budget(&budget, &spend, &[coin(10, DENOM)]).unwrap();
+    assert_eq!(
+        check_budget(&budget, &spend, &[coin(15, DENOM)]).unwrap_err(),
+        ContractError::BudgetExceeded {
+            denom: DENOM.into(),
+            limit: Uint128::new(20),
+            amount: Uint128::new(25),
+        }
+    );
+}
@@ -1,10 +1,16 @@
 pub use crate::contract::{
-    CronKittyActionResp, CronKittyPlugin, ExecMsg as CronKittyExecMsg,
-    InstantiateMsg as CronKittyInstMsg, QueryMsg as CronKittyQueryMsg,
+    ActionStatus, CronKittyActionResp, CronKittyBudget, CronKittyBudgetResp, CronKittyConfigResp,
+    CronKittyExecutionHistoryResp, CronKittyExecutionOutcome, CronKittyExecutionSlot,
+    CronKittyFailureLimit, CronKittyOnFailureLimit, CronKittyPlugin, CronKittyReceiveMsg,
+    CronKittySchedule, CronKittySpendLimit, CronKittyTaskBalanceResp, CronKittyTaskCostResp,
+    ExecMsg as CronKittyExecMsg, InstantiateMsg as CronKittyInstMsg,
+    MigrateMsg as CronKittyMigrateMsg, QueryMsg as CronKittyQueryMsg,
 };
+use crate::error::ContractError;
 use crate::tests::{croncat_helpers::*, helpers::*};
 use cosmwasm_std::{
-    coin, to_binary, Addr, BankMsg, CosmosMsg, QueryRequest, StdError, Uint128, WasmQuery,
+    coin, from_slice, to_binary, Addr, BankMsg, CosmosMsg, Empty, QueryRequest, StdError, Uint128,
+    WasmQuery,
 };
 use croncat_sdk_manager::{
     msg::{ManagerExecuteMsg, ManagerQueryMsg},
@@ -14,7 +20,12 @@ use croncat_sdk_tasks::{
     msg::{TasksExecuteMsg, TasksQueryMsg},
     types::{Action, Interval, TaskInfo, TaskRequest, TaskResponse},
 };
+use cw20::{
+    BalanceResponse as Cw20BalanceResponse, Cw20Coin, Cw20CoinVerified, Cw20ExecuteMsg,
+    Cw20QueryMsg,
+};
 use cw_multi_test::Executor;
+use cw_utils::PaymentError;
 use vectis_contract_tests::common::{
     base_common::HubChainSuite,
     common::{proxy_exec, INSTALL_FEE, REGISTRY_FEE},
@@ -187,7 +198,21 @@ fn remove_task_works() {
     let mut suite = HubChainSuite::init().unwrap();
     let cc_contracts =
         setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
-    let (_task_on_croncat, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+    let (task_on_croncat, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    let task_balance: TaskBalanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.manager,
+            &ManagerQueryMsg::TaskBalance {
+                task_hash: task_on_croncat.task_hash.clone(),
+            },
+        )
+        .unwrap();
+    let refund = task_balance.balance.unwrap().native_balance;
+    let proxy_balance_before = suite.app.wrap().query_balance(&proxy, DENOM).unwrap();
+
     suite
         .app
         .execute_contract(
@@ -203,6 +228,15 @@ fn remove_task_works() {
         .unwrap();
     suite.fast_forward_block_time(10000);
 
+    // Refund is forwarded to the proxy
+    let proxy_balance_after = suite.app.wrap().query_balance(&proxy, DENOM).unwrap();
+    assert_eq!(
+        proxy_balance_after.amount - proxy_balance_before.amount,
+        refund
+    );
+    let cronkitty_balance = suite.app.wrap().query_balance(&cronkitty, DENOM).unwrap();
+    assert!(cronkitty_balance.amount.is_zero());
+
     // Removed on croncat
     let after_remove_task: Vec<TaskResponse> = suite
         .app
@@ -287,6 +321,51 @@ fn insufficient_fee_cannot_create_task() {
     );
 }
 
+#[test]
+fn insufficient_fee_is_rejected_before_croncat() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+
+    register_cronkitty(&mut suite, REGISTRY_FEE);
+    let (proxy, cronkitty) = set_up_proxy_and_install_cronkitty(
+        &mut suite,
+        INSTALL_FEE,
+        1,
+        &cc_contracts.factory_addr,
+        100_000,
+    );
+
+    let msg = CosmosMsg::Bank(BankMsg::Burn {
+        amount: vec![coin(100, DENOM)],
+    });
+    let gas_limit = 150_000u64;
+    let required = required_deposit(&suite, &cronkitty, gas_limit, msg.clone());
+    let provided = coin(required.amount.u128() / 4, DENOM);
+
+    let err = suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::CreateTask {
+                    task: task_request(gas_limit, msg),
+                    label: None,
+                },
+                vec![provided.clone()],
+            ),
+            &[],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::NotEnoughFundsForGas { required, provided }.to_string()
+    );
+}
+
 #[test]
 fn cronkitty_actions_cannot_execute_by_other_tasks() {
     let mut suite = HubChainSuite::init().unwrap();
@@ -445,3 +524,2547 @@ fn plugin_info_is_correct() {
         .unwrap();
     assert_eq!(&version_details.ipfs_hash, "some-hash")
 }
+
+#[test]
+fn list_actions_works() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (task_on_croncat, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    let gas_limit = 150_000u64;
+    let required = GasPrice::default()
+        .calculate(gas_limit + AGENT_FEE + TREASURY_FEE + GAS_BASE_FEE + GAS_ACTION_FEE)
+        .unwrap()
+        * 2;
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: suite.deployer.to_string(),
+        amount: vec![coin(1, DENOM)],
+    });
+    suite
+        .app
+        .send_tokens(
+            suite.controller.clone(),
+            proxy.clone(),
+            &[coin(required, DENOM)],
+        )
+        .unwrap();
+    create_task(
+        &mut suite,
+        &proxy,
+        &cronkitty,
+        gas_limit,
+        coin(required, DENOM),
+        msg.clone(),
+        &cc_contracts.tasks_addr,
+    );
+
+    let actions: Vec<(u64, CronKittyActionResp)> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::ListActions {
+                start_after: None,
+                limit: None,
+                include_pending: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(actions.len(), 2);
+    assert_eq!(actions[0].0, 0);
+    assert_eq!(
+        actions[0].1.task_hash.as_ref().unwrap(),
+        &task_on_croncat.task_hash
+    );
+    assert_eq!(actions[1].0, 1);
+    assert_eq!(actions[1].1.msgs[0], msg);
+    assert_eq!(actions[1].1.task_addr, cc_contracts.tasks_addr);
+    assert_eq!(actions[1].1.manager_addr, cc_contracts.manager);
+
+    let actions: Vec<(u64, CronKittyActionResp)> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::ListActions {
+                start_after: Some(0),
+                limit: Some(1),
+                include_pending: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].0, 1);
+}
+
+#[test]
+fn migrate_from_v0_2_works() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+
+    let legacy_code_id = suite.app.store_code(legacy_cronkitty_contract());
+    let cronkitty_code_id = suite.app.store_code(Box::new(CronKittyPlugin::new()));
+
+    let msg = CosmosMsg::Bank(BankMsg::Burn {
+        amount: vec![coin(1, DENOM)],
+    });
+    let cronkitty = suite
+        .app
+        .instantiate_contract(
+            legacy_code_id,
+            suite.deployer.clone(),
+            &LegacyCronKittyInstMsg {
+                contract_version: ("cronkitty".into(), "0.2.1".into()),
+                croncat_factory_addr: cc_contracts.factory_addr.to_string(),
+                vectis_account_addr: suite.controller.to_string(),
+                actions: vec![
+                    (0, ([0, 1], vec![msg.clone()], Some("task-hash".into()))),
+                    (1, ([0, 1], vec![msg.clone()], None)),
+                ],
+            },
+            &[],
+            "legacy-cronkitty",
+            Some(suite.deployer.to_string()),
+        )
+        .unwrap();
+
+    suite
+        .app
+        .migrate_contract(
+            suite.deployer.clone(),
+            cronkitty.clone(),
+            &CronKittyMigrateMsg {
+                croncat_factory_addr: None,
+            },
+            cronkitty_code_id,
+        )
+        .unwrap();
+
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+
+    assert_eq!(action.msgs, vec![msg.clone()]);
+    assert_eq!(action.task_addr, cc_contracts.tasks_addr);
+    assert_eq!(action.manager_addr, cc_contracts.manager);
+    assert_eq!(action.created_at, None);
+    // "task-hash" is not on croncat
+    assert_eq!(action.task_hash, None);
+    assert_eq!(action.status, ActionStatus::Ended);
+    assert_eq!(action.schedule, None);
+
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 1 })
+        .unwrap();
+
+    assert_eq!(action.task_hash, None);
+    assert_eq!(action.status, ActionStatus::Pending);
+
+    suite
+        .app
+        .wrap()
+        .query_wasm_smart::<(u64, CronKittyActionResp)>(
+            &cronkitty,
+            &CronKittyQueryMsg::ActionByTaskHash {
+                task_hash: "task-hash".into(),
+            },
+        )
+        .unwrap_err();
+
+    let config: CronKittyConfigResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.active_actions, 0);
+
+    let contract_version: cw2::ContractVersion = from_slice(
+        &suite
+            .app
+            .wrap()
+            .query_wasm_raw(cronkitty.to_string(), b"contract_info".to_vec())
+            .unwrap()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(contract_version.version, env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn migrate_rejects_downgrade_and_other_contracts() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+
+    let legacy_code_id = suite.app.store_code(legacy_cronkitty_contract());
+    let cronkitty_code_id = suite.app.store_code(Box::new(CronKittyPlugin::new()));
+
+    let cases = [
+        (
+            "cronkitty",
+            "99.0.0",
+            ContractError::MigrationDowngrade {
+                from: "99.0.0".into(),
+                to: env!("CARGO_PKG_VERSION").into(),
+            },
+        ),
+        (
+            "not-cronkitty",
+            "0.2.1",
+            ContractError::InvalidMigration {
+                contract: "not-cronkitty".into(),
+                version: "0.2.1".into(),
+            },
+        ),
+    ];
+    for (contract, version, expected) in cases {
+        let addr = suite
+            .app
+            .instantiate_contract(
+                legacy_code_id,
+                suite.deployer.clone(),
+                &LegacyCronKittyInstMsg {
+                    contract_version: (contract.into(), version.into()),
+                    croncat_factory_addr: cc_contracts.factory_addr.to_string(),
+                    vectis_account_addr: suite.controller.to_string(),
+                    actions: vec![],
+                },
+                &[],
+                "legacy-cronkitty",
+                Some(suite.deployer.to_string()),
+            )
+            .unwrap();
+
+        let err = suite
+            .app
+            .migrate_contract(
+                suite.deployer.clone(),
+                addr,
+                &CronKittyMigrateMsg {
+                    croncat_factory_addr: None,
+                },
+                cronkitty_code_id,
+            )
+            .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), expected.to_string());
+    }
+}
+
+#[test]
+fn update_task_works() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (task_on_croncat, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    let balance_before: TaskBalanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.manager,
+            &ManagerQueryMsg::TaskBalance {
+                task_hash: task_on_croncat.task_hash.clone(),
+            },
+        )
+        .unwrap();
+
+    // msgs only
+    let new_msg = CosmosMsg::Bank(BankMsg::Burn {
+        amount: vec![coin(2, DENOM)],
+    });
+
+    // funds are not used when the task is not recreated
+    let err = suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::UpdateTask {
+                    action_id: 0,
+                    msgs: Some(vec![new_msg.clone()]),
+                    interval: None,
+                    boundary: None,
+                },
+                vec![coin(10, DENOM)],
+            ),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::Payment(PaymentError::NonPayable {}).to_string()
+    );
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::UpdateTask {
+                    action_id: 0,
+                    msgs: Some(vec![new_msg.clone()]),
+                    interval: None,
+                    boundary: None,
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+    assert_eq!(action.msgs, vec![new_msg.clone()]);
+    assert_eq!(action.task_hash.unwrap(), task_on_croncat.task_hash);
+
+    // schedule change recreates the task with the same action id
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::UpdateTask {
+                    action_id: 0,
+                    msgs: None,
+                    interval: Some(Interval::Block(10)),
+                    boundary: None,
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let tasks_on_croncat: Vec<TaskInfo> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.tasks_addr,
+            &TasksQueryMsg::TasksByOwner {
+                owner_addr: cronkitty.to_string(),
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(tasks_on_croncat.len(), 1);
+    assert_eq!(tasks_on_croncat[0].interval, Interval::Block(10));
+
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+    assert_eq!(action.msgs, vec![new_msg]);
+    assert_eq!(action.status, ActionStatus::Active);
+    assert_eq!(
+        action.schedule.as_ref().unwrap().interval,
+        Interval::Block(10)
+    );
+    assert_eq!(
+        action.task_hash.as_ref().unwrap(),
+        &tasks_on_croncat[0].task_hash
+    );
+    assert_ne!(action.task_hash.unwrap(), task_on_croncat.task_hash);
+
+    let balance_after: TaskBalanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.manager,
+            &ManagerQueryMsg::TaskBalance {
+                task_hash: tasks_on_croncat[0].task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        balance_after.balance.unwrap().native_balance,
+        balance_before.balance.unwrap().native_balance
+    );
+
+    // next action id is unchanged
+    let next_id: u64 = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::ActionId {})
+        .unwrap();
+    assert_eq!(next_id, 1);
+}
+
+#[test]
+fn paused_action_is_skipped_until_resumed() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (_, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::PauseAction { action_id: 0 },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+    assert_eq!(action.status, ActionStatus::Paused);
+
+    suite.fast_forward_block_time(10000);
+    let res = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(AGENT),
+            cc_contracts.manager.clone(),
+            &ManagerExecuteMsg::ProxyCall { task_hash: None },
+            &vec![],
+        )
+        .unwrap();
+    assert!(res
+        .events
+        .iter()
+        .any(|event| event.ty == "wasm-vectis.cronkitty.v1.MsgExecuteSkipped"));
+    assert!(!res.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "vectis.proxy.v1/PluginExecMsg")));
+
+    // task is still on croncat
+    let tasks: Vec<TaskInfo> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.tasks_addr,
+            &TasksQueryMsg::TasksByOwner {
+                owner_addr: cronkitty.to_string(),
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(tasks.len(), 1);
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::ResumeAction { action_id: 0 },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    suite.fast_forward_block_time(10000);
+    let res = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(AGENT),
+            cc_contracts.manager.clone(),
+            &ManagerExecuteMsg::ProxyCall { task_hash: None },
+            &vec![],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|event| event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "vectis.proxy.v1/PluginExecMsg")));
+}
+
+#[test]
+fn sweep_funds_works() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (_, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    let stray_amount = 1_000u128;
+    suite
+        .app
+        .send_tokens(
+            suite.controller.clone(),
+            cronkitty.clone(),
+            &[coin(stray_amount, DENOM)],
+        )
+        .unwrap();
+    let proxy_balance_before = suite.app.wrap().query_balance(&proxy, DENOM).unwrap();
+
+    suite
+        .app
+        .execute_contract(
+            suite.deployer.clone(),
+            cronkitty.clone(),
+            &CronKittyExecMsg::SweepFunds { denoms: None },
+            &[],
+        )
+        .unwrap_err();
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::SweepFunds {
+                    denoms: Some(vec![DENOM.to_string()]),
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let proxy_balance_after = suite.app.wrap().query_balance(&proxy, DENOM).unwrap();
+    assert_eq!(
+        proxy_balance_after.amount - proxy_balance_before.amount,
+        Uint128::from(stray_amount)
+    );
+}
+
+#[test]
+fn task_balance_query_works() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (task_on_croncat, _, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    let on_croncat: TaskBalanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.manager,
+            &ManagerQueryMsg::TaskBalance {
+                task_hash: task_on_croncat.task_hash.clone(),
+            },
+        )
+        .unwrap();
+
+    let on_cronkitty: CronKittyTaskBalanceResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::TaskBalance { action_id: 0 })
+        .unwrap();
+
+    assert_eq!(on_cronkitty.balance, on_croncat.balance);
+    assert_eq!(on_cronkitty.executions_remaining, Some(2));
+}
+
+#[test]
+fn estimate_task_cost_works() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (_, _, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    // Two actions are collapsed into one on croncat
+    let gas_limit = 150_000u64;
+    let msg = CosmosMsg::Bank(BankMsg::Burn {
+        amount: vec![coin(1, DENOM)],
+    });
+    let mut task = task_request(gas_limit / 2, msg.clone());
+    task.actions.push(Action {
+        msg,
+        gas_limit: Some(gas_limit / 2),
+    });
+
+    let estimate: CronKittyTaskCostResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::EstimateTaskCost {
+                task: task.clone(),
+                executions: Some(10),
+            },
+        )
+        .unwrap();
+
+    let per_execution = GasPrice::default()
+        .calculate(gas_limit + AGENT_FEE + TREASURY_FEE + GAS_BASE_FEE + GAS_ACTION_FEE)
+        .unwrap();
+    assert_eq!(estimate.gas, gas_limit + GAS_BASE_FEE + GAS_ACTION_FEE);
+    assert_eq!(estimate.cost_per_execution, coin(per_execution, DENOM));
+    assert_eq!(estimate.min_deposit, coin(per_execution * 2, DENOM));
+    assert_eq!(
+        estimate.cost_for_executions,
+        Some(coin(per_execution * 10, DENOM))
+    );
+
+    // One-off tasks only require one execution
+    task.interval = Interval::Once;
+    let estimate: CronKittyTaskCostResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::EstimateTaskCost {
+                task,
+                executions: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(estimate.min_deposit, coin(per_execution, DENOM));
+    assert_eq!(estimate.cost_for_executions, None);
+}
+
+#[test]
+fn sync_actions_marks_tasks_removed_by_croncat() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (_, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    // A one-off task is removed by croncat once executed
+    let gas_limit = 150_000u64;
+    let msg = CosmosMsg::Bank(BankMsg::Burn {
+        amount: vec![coin(1, DENOM)],
+    });
+    let mut task = task_request(gas_limit, msg);
+    task.interval = Interval::Once;
+    let estimate: CronKittyTaskCostResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::EstimateTaskCost {
+                task: task.clone(),
+                executions: None,
+            },
+        )
+        .unwrap();
+    // extra funds are refunded when the task is removed
+    let deposit = coin(estimate.min_deposit.amount.u128() * 2, DENOM);
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::CreateTask { task, label: None },
+                vec![deposit.clone()],
+            ),
+            &[deposit],
+        )
+        .unwrap();
+
+    suite.fast_forward_block_time(10000);
+    // executes the tasks in order they are ready
+    for _ in 0..2 {
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(AGENT),
+                cc_contracts.manager.clone(),
+                &ManagerExecuteMsg::ProxyCall { task_hash: None },
+                &vec![],
+            )
+            .unwrap();
+    }
+
+    let proxy_balance_before = suite.app.wrap().query_balance(&proxy, DENOM).unwrap();
+    let res = suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::SyncActions {
+                    start_after: None,
+                    limit: None,
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let synced = res
+        .events
+        .iter()
+        .filter(|event| event.ty == "wasm-vectis.cronkitty.v1.SyncAction")
+        .count();
+    assert_eq!(synced, 1);
+
+    // the recurring task is untouched
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+    assert_eq!(action.status, ActionStatus::Active);
+
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 1 })
+        .unwrap();
+    assert_eq!(action.status, ActionStatus::Ended);
+    assert_eq!(action.task_hash, None);
+
+    // ended actions are listed by default
+    let actions: Vec<(u64, CronKittyActionResp)> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::ListActions {
+                start_after: None,
+                limit: None,
+                include_pending: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(actions.len(), 2);
+
+    // croncat refund to cronkitty is forwarded to the proxy
+    let proxy_balance_after = suite.app.wrap().query_balance(&proxy, DENOM).unwrap();
+    assert!(proxy_balance_after.amount > proxy_balance_before.amount);
+
+    // the ended action is removed without croncat
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::RemoveTask { task_id: 1 },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .wrap()
+        .query_wasm_smart::<CronKittyActionResp>(
+            &cronkitty,
+            &CronKittyQueryMsg::Action { action_id: 1 },
+        )
+        .unwrap_err();
+}
+
+#[test]
+fn action_by_task_hash_works() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (task_on_croncat, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    let (action_id, action): (u64, CronKittyActionResp) = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::ActionByTaskHash {
+                task_hash: task_on_croncat.task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(action_id, 0);
+    assert_eq!(action.task_hash.unwrap(), task_on_croncat.task_hash);
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::RemoveTask { task_id: 0 },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    suite
+        .app
+        .wrap()
+        .query_wasm_smart::<(u64, CronKittyActionResp)>(
+            &cronkitty,
+            &CronKittyQueryMsg::ActionByTaskHash {
+                task_hash: task_on_croncat.task_hash,
+            },
+        )
+        .unwrap_err();
+}
+
+#[test]
+fn create_and_remove_tasks_in_batch_works() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    register_cronkitty(&mut suite, REGISTRY_FEE);
+    let (proxy, cronkitty) = set_up_proxy_and_install_cronkitty(
+        &mut suite,
+        INSTALL_FEE,
+        1,
+        &cc_contracts.factory_addr,
+        1_000_000,
+    );
+
+    let gas_limit = 150_000u64;
+    let msg = CosmosMsg::Bank(BankMsg::Burn {
+        amount: vec![coin(1, DENOM)],
+    });
+    let required = required_deposit(&suite, &cronkitty, gas_limit, msg.clone());
+    let tasks = vec![
+        (task_request(gas_limit, msg.clone()), vec![required.clone()]),
+        (task_request(gas_limit, msg.clone()), vec![required.clone()]),
+    ];
+
+    // funds must add up to the funds of each task
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::CreateTasks {
+                    tasks: tasks.clone(),
+                },
+                vec![required.clone()],
+            ),
+            &[],
+        )
+        .unwrap_err();
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::CreateTasks { tasks },
+                vec![coin(required.amount.u128() * 2, DENOM)],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let tasks_on_croncat: Vec<TaskInfo> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.tasks_addr,
+            &TasksQueryMsg::TasksByOwner {
+                owner_addr: cronkitty.to_string(),
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(tasks_on_croncat.len(), 2);
+
+    let next_id: u64 = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::ActionId {})
+        .unwrap();
+    assert_eq!(next_id, 2);
+
+    for action_id in 0..2u64 {
+        let action: CronKittyActionResp = suite
+            .app
+            .wrap()
+            .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id })
+            .unwrap();
+        assert_eq!(action.status, ActionStatus::Active);
+        assert!(tasks_on_croncat
+            .iter()
+            .any(|t| Some(&t.task_hash) == action.task_hash.as_ref()));
+    }
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::RemoveTasks {
+                    action_ids: vec![0, 1],
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let tasks_on_croncat: Vec<TaskInfo> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.tasks_addr,
+            &TasksQueryMsg::TasksByOwner {
+                owner_addr: cronkitty.to_string(),
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(tasks_on_croncat.is_empty());
+
+    let actions: Vec<(u64, CronKittyActionResp)> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::ListActions {
+                start_after: None,
+                limit: None,
+                include_pending: Some(true),
+            },
+        )
+        .unwrap();
+    assert!(actions.is_empty());
+}
+
+#[test]
+fn teardown_removes_all_tasks_and_rejects_new_ones() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (task_on_croncat, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    let task_balance: TaskBalanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.manager,
+            &ManagerQueryMsg::TaskBalance {
+                task_hash: task_on_croncat.task_hash.clone(),
+            },
+        )
+        .unwrap();
+    let refund = task_balance.balance.unwrap().native_balance;
+    let proxy_balance_before = suite.app.wrap().query_balance(&proxy, DENOM).unwrap();
+
+    // only the owner can tear down
+    suite
+        .app
+        .execute_contract(
+            suite.deployer.clone(),
+            cronkitty.clone(),
+            &CronKittyExecMsg::Teardown { limit: None },
+            &[],
+        )
+        .unwrap_err();
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::Teardown { limit: None },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let proxy_balance_after = suite.app.wrap().query_balance(&proxy, DENOM).unwrap();
+    assert_eq!(
+        proxy_balance_after.amount - proxy_balance_before.amount,
+        refund
+    );
+
+    let tasks_on_croncat: Vec<TaskResponse> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.tasks_addr,
+            &TasksQueryMsg::Tasks {
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(tasks_on_croncat.is_empty());
+
+    let actions: Vec<(u64, CronKittyActionResp)> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::ListActions {
+                start_after: None,
+                limit: None,
+                include_pending: Some(true),
+            },
+        )
+        .unwrap();
+    assert!(actions.is_empty());
+
+    let msg = CosmosMsg::Bank(BankMsg::Burn {
+        amount: vec![coin(1, DENOM)],
+    });
+    let required = required_deposit(&suite, &cronkitty, 150_000, msg.clone());
+    let err = suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::CreateTask {
+                    task: task_request(150_000, msg),
+                    label: None,
+                },
+                vec![required],
+            ),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::Terminated.to_string()
+    );
+}
+
+#[test]
+fn teardown_is_paginated() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    register_cronkitty(&mut suite, REGISTRY_FEE);
+    let (proxy, cronkitty) = set_up_proxy_and_install_cronkitty(
+        &mut suite,
+        INSTALL_FEE,
+        1,
+        &cc_contracts.factory_addr,
+        1_000_000,
+    );
+
+    let gas_limit = 150_000u64;
+    let msg = CosmosMsg::Bank(BankMsg::Burn {
+        amount: vec![coin(1, DENOM)],
+    });
+    let required = required_deposit(&suite, &cronkitty, gas_limit, msg.clone());
+    for _ in 0..2 {
+        create_task(
+            &mut suite,
+            &proxy,
+            &cronkitty,
+            gas_limit,
+            required.clone(),
+            msg.clone(),
+            &cc_contracts.tasks_addr,
+        );
+    }
+
+    let teardown = |suite: &mut HubChainSuite, limit: Option<u32>| {
+        let res = suite
+            .app
+            .execute_contract(
+                suite.controller.clone(),
+                proxy.clone(),
+                &proxy_exec(&cronkitty, &CronKittyExecMsg::Teardown { limit }, vec![]),
+                &[],
+            )
+            .unwrap();
+        let event = res
+            .events
+            .iter()
+            .find(|e| e.ty == "wasm-vectis.cronkitty.v1.MsgTeardown")
+            .unwrap();
+        event
+            .attributes
+            .iter()
+            .find(|a| a.key == "Completed")
+            .unwrap()
+            .value
+            .clone()
+    };
+    let action_ids = |suite: &HubChainSuite| -> Vec<u64> {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart::<Vec<(u64, CronKittyActionResp)>>(
+                &cronkitty,
+                &CronKittyQueryMsg::ListActions {
+                    start_after: None,
+                    limit: None,
+                    include_pending: Some(true),
+                },
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect()
+    };
+
+    // a zero limit still removes an action
+    assert_eq!(teardown(&mut suite, Some(0)), "false");
+    assert_eq!(action_ids(&suite), vec![1]);
+    let config: CronKittyConfigResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Config {})
+        .unwrap();
+    assert!(config.terminated);
+
+    assert_eq!(teardown(&mut suite, Some(1)), "true");
+    assert!(action_ids(&suite).is_empty());
+
+    let tasks_on_croncat: Vec<TaskInfo> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.tasks_addr,
+            &TasksQueryMsg::TasksByOwner {
+                owner_addr: cronkitty.to_string(),
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(tasks_on_croncat.is_empty());
+}
+
+#[test]
+fn config_query_works() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (_task_on_croncat, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    let config: CronKittyConfigResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.owner, proxy);
+    assert_eq!(config.croncat_factory, cc_contracts.factory_addr);
+    assert_eq!(config.next_action_id, 1);
+    assert_eq!(config.active_actions, 1);
+    assert_eq!(config.paused_actions, 0);
+    assert_eq!(config.contract_version.contract, env!("CARGO_PKG_NAME"));
+    assert_eq!(config.contract_version.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(config.croncat_tasks_version, Some([0, 1]));
+    assert_eq!(config.croncat_manager_version, Some([0, 1]));
+    assert!(!config.terminated);
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::PauseAction { action_id: 0 },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let config: CronKittyConfigResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.active_actions, 0);
+    assert_eq!(config.paused_actions, 1);
+}
+
+#[test]
+fn update_croncat_factory_works() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (_task_on_croncat, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+    let new_cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+
+    // only the owner can update
+    suite
+        .app
+        .execute_contract(
+            suite.deployer.clone(),
+            cronkitty.clone(),
+            &CronKittyExecMsg::UpdateCroncatFactory {
+                addr: new_cc_contracts.factory_addr.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+
+    // not a croncat factory
+    let err = suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::UpdateCroncatFactory {
+                    addr: cronkitty.to_string(),
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::NoCronCatVersion {
+            name: "tasks".to_string()
+        }
+        .to_string()
+    );
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::UpdateCroncatFactory {
+                    addr: new_cc_contracts.factory_addr.to_string(),
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let config: CronKittyConfigResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.croncat_factory, new_cc_contracts.factory_addr);
+
+    // existing action is still resolved on the factory it was created on
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+    assert_eq!(action.task_addr, cc_contracts.tasks_addr);
+    assert_eq!(action.manager_addr, cc_contracts.manager);
+
+    // new tasks are created on the new factory
+    let msg = CosmosMsg::Bank(BankMsg::Burn {
+        amount: vec![coin(1, DENOM)],
+    });
+    let required = required_deposit(&suite, &cronkitty, 150_000, msg.clone());
+    let tasks_on_croncat = create_task(
+        &mut suite,
+        &proxy,
+        &cronkitty,
+        150_000,
+        required,
+        msg,
+        &new_cc_contracts.tasks_addr,
+    );
+    assert_eq!(tasks_on_croncat.len(), 1);
+
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 1 })
+        .unwrap();
+    assert_eq!(action.task_addr, new_cc_contracts.tasks_addr);
+}
+
+#[test]
+fn migrate_action_to_latest_works() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (task_on_croncat, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    let err = suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::MigrateActionToLatest { action_id: 0 },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::ActionOnLatestVersion.to_string()
+    );
+
+    // croncat redeploys, the new factory has the latest tasks and manager
+    let new_cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::UpdateCroncatFactory {
+                    addr: new_cc_contracts.factory_addr.to_string(),
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let task_balance: TaskBalanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.manager,
+            &ManagerQueryMsg::TaskBalance {
+                task_hash: task_on_croncat.task_hash.clone(),
+            },
+        )
+        .unwrap();
+    let balance = task_balance.balance.unwrap().native_balance;
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::MigrateActionToLatest { action_id: 0 },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let old_tasks: Vec<TaskResponse> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cc_contracts.tasks_addr,
+            &TasksQueryMsg::Tasks {
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(old_tasks.is_empty());
+
+    let new_tasks: Vec<TaskInfo> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &new_cc_contracts.tasks_addr,
+            &TasksQueryMsg::TasksByOwner {
+                owner_addr: cronkitty.to_string(),
+                from_index: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(new_tasks.len(), 1);
+    assert_eq!(new_tasks[0].interval, task_on_croncat.interval);
+    assert_eq!(new_tasks[0].actions, task_on_croncat.actions);
+
+    // the refunded balance is moved to the new task
+    let task_balance: TaskBalanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &new_cc_contracts.manager,
+            &ManagerQueryMsg::TaskBalance {
+                task_hash: new_tasks[0].task_hash.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(task_balance.balance.unwrap().native_balance, balance);
+
+    // same action id
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+    assert_eq!(action.status, ActionStatus::Active);
+    assert_eq!(action.task_hash, Some(new_tasks[0].task_hash.clone()));
+    assert_eq!(action.task_addr, new_cc_contracts.tasks_addr);
+
+    // actions on the latest version are skipped in batch
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::MigrateActionsToLatest {
+                    action_ids: vec![0],
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+    assert_eq!(action.task_hash, Some(new_tasks[0].task_hash.clone()));
+}
+
+#[test]
+fn action_schedule_is_stored_and_backfilled() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (task_on_croncat, _proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+    assert_eq!(
+        action.schedule,
+        Some(CronKittySchedule {
+            interval: Interval::Block(5),
+            boundary: None,
+            stop_on_fail: false,
+            queries: None,
+            transforms: None,
+            gas_limit: Some(150_000),
+        })
+    );
+
+    // 0.2.x actions are backfilled from the task on croncat
+    let legacy_code_id = suite.app.store_code(legacy_cronkitty_contract());
+    let cronkitty_code_id = suite.app.store_code(Box::new(CronKittyPlugin::new()));
+    let legacy = suite
+        .app
+        .instantiate_contract(
+            legacy_code_id,
+            suite.deployer.clone(),
+            &LegacyCronKittyInstMsg {
+                contract_version: ("cronkitty".into(), "0.2.1".into()),
+                croncat_factory_addr: cc_contracts.factory_addr.to_string(),
+                vectis_account_addr: suite.controller.to_string(),
+                actions: vec![
+                    (
+                        0,
+                        (
+                            [0, 1],
+                            action.msgs.clone(),
+                            Some(task_on_croncat.task_hash.clone()),
+                        ),
+                    ),
+                    // a croncat version the factory does not know about
+                    (1, ([9, 9], action.msgs.clone(), Some("gone".into()))),
+                ],
+            },
+            &[],
+            "legacy-cronkitty",
+            Some(suite.deployer.to_string()),
+        )
+        .unwrap();
+    suite
+        .app
+        .migrate_contract(
+            suite.deployer.clone(),
+            legacy.clone(),
+            &CronKittyMigrateMsg {
+                croncat_factory_addr: None,
+            },
+            cronkitty_code_id,
+        )
+        .unwrap();
+
+    let migrated: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&legacy, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+    let schedule = migrated.schedule.unwrap();
+    assert_eq!(schedule.interval, Interval::Block(5));
+    assert!(!schedule.stop_on_fail);
+    assert_eq!(schedule.gas_limit, Some(150_000));
+
+    // the action on a version gone from the factory has ended
+    let migrated: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&legacy, &CronKittyQueryMsg::Action { action_id: 1 })
+        .unwrap();
+    assert_eq!(migrated.status, ActionStatus::Ended);
+    assert_eq!(migrated.task_hash, None);
+    assert_eq!(migrated.schedule, None);
+    assert_eq!(migrated.task_addr, cc_contracts.tasks_addr);
+
+    let actions: Vec<(u64, CronKittyActionResp)> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &legacy,
+            &CronKittyQueryMsg::ListActions {
+                start_after: None,
+                limit: None,
+                include_pending: Some(true),
+            },
+        )
+        .unwrap();
+    assert_eq!(actions.len(), 2);
+
+    let config: CronKittyConfigResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&legacy, &CronKittyQueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.active_actions, 1);
+}
+
+#[test]
+fn next_executions_query_works() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (_task_on_croncat, proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    // the task runs every 5 blocks
+    let height = suite.app.block_info().height;
+    let first = (height / 5 + 1) * 5;
+    let next: Vec<CronKittyExecutionSlot> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::NextExecutions {
+                action_id: 0,
+                count: Some(3),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        next,
+        vec![
+            CronKittyExecutionSlot::Block(first),
+            CronKittyExecutionSlot::Block(first + 5),
+            CronKittyExecutionSlot::Block(first + 10),
+        ]
+    );
+
+    // paused actions are not executed
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::PauseAction { action_id: 0 },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+    let next: Vec<CronKittyExecutionSlot> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::NextExecutions {
+                action_id: 0,
+                count: None,
+            },
+        )
+        .unwrap();
+    assert!(next.is_empty());
+}
+
+#[test]
+fn execution_history_is_recorded() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    let (task_on_croncat, _proxy, cronkitty) = mock_setup_a_task(&mut suite, &cc_contracts);
+
+    let history: CronKittyExecutionHistoryResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::ExecutionHistory {
+                action_id: 0,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(history.executions, 0);
+    assert!(history.history.is_empty());
+
+    for _ in 0..2 {
+        suite.fast_forward_block_time(10000);
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(AGENT),
+                cc_contracts.manager.clone(),
+                &ManagerExecuteMsg::ProxyCall { task_hash: None },
+                &[],
+            )
+            .unwrap();
+    }
+
+    let history: CronKittyExecutionHistoryResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::ExecutionHistory {
+                action_id: 0,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(history.executions, 2);
+    assert_eq!(history.history.len(), 2);
+    // newest first
+    assert!(history.history[0].height > history.history[1].height);
+    assert_eq!(
+        history.history[0].task_execution_info.task_hash,
+        task_on_croncat.task_hash
+    );
+    assert_eq!(
+        history.history[0].outcome,
+        Some(CronKittyExecutionOutcome::Success)
+    );
+
+    let history: CronKittyExecutionHistoryResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::ExecutionHistory {
+                action_id: 0,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(history.executions, 2);
+    assert_eq!(history.history.len(), 1);
+    assert_eq!(history.history[0].height, suite.app.block_info().height);
+}
+
+#[test]
+fn failed_proxy_execution_is_recorded() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    register_cronkitty(&mut suite, REGISTRY_FEE);
+    let (proxy, cronkitty) = set_up_proxy_and_install_cronkitty(
+        &mut suite,
+        INSTALL_FEE,
+        1,
+        &cc_contracts.factory_addr,
+        100_000,
+    );
+
+    // the proxy does not have enough to send
+    let gas_limit = 150_000u64;
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: suite.deployer.to_string(),
+        amount: vec![coin(1_000_000_000, DENOM)],
+    });
+    let required = required_deposit(&suite, &cronkitty, gas_limit, msg.clone());
+    create_task(
+        &mut suite,
+        &proxy,
+        &cronkitty,
+        gas_limit,
+        required,
+        msg,
+        &cc_contracts.tasks_addr,
+    );
+
+    suite.fast_forward_block_time(10000);
+    let res = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(AGENT),
+            cc_contracts.manager.clone(),
+            &ManagerExecuteMsg::ProxyCall { task_hash: None },
+            &[],
+        )
+        .unwrap();
+    assert!(res
+        .events
+        .iter()
+        .any(|e| e.ty == "wasm-vectis.cronkitty.v1.ReplyProxyExecuteFailed"));
+
+    let history: CronKittyExecutionHistoryResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::ExecutionHistory {
+                action_id: 0,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(history.executions, 1);
+    assert!(matches!(
+        history.history[0].outcome,
+        Some(CronKittyExecutionOutcome::Failure { .. })
+    ));
+}
+
+#[test]
+fn action_is_paused_after_consecutive_failures() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    register_cronkitty(&mut suite, REGISTRY_FEE);
+    let (proxy, cronkitty) = set_up_proxy_and_install_cronkitty(
+        &mut suite,
+        INSTALL_FEE,
+        1,
+        &cc_contracts.factory_addr,
+        1_000_000,
+    );
+
+    // the proxy does not have enough to send
+    let gas_limit = 150_000u64;
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: suite.deployer.to_string(),
+        amount: vec![coin(1_000_000_000, DENOM)],
+    });
+    let required = required_deposit(&suite, &cronkitty, gas_limit, msg.clone());
+    let required = coin(required.amount.u128() * 2, DENOM);
+    create_task(
+        &mut suite,
+        &proxy,
+        &cronkitty,
+        gas_limit,
+        required,
+        msg,
+        &cc_contracts.tasks_addr,
+    );
+
+    let err = suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::SetFailureLimit {
+                    action_id: 0,
+                    limit: Some(CronKittyFailureLimit {
+                        max_consecutive_failures: 0,
+                        on_limit: CronKittyOnFailureLimit::Pause,
+                    }),
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::InvalidFailureLimit.to_string()
+    );
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::SetFailureLimit {
+                    action_id: 0,
+                    limit: Some(CronKittyFailureLimit {
+                        max_consecutive_failures: 2,
+                        on_limit: CronKittyOnFailureLimit::Pause,
+                    }),
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let mut limit_reached = vec![];
+    for _ in 0..2 {
+        suite.fast_forward_block_time(10000);
+        let res = suite
+            .app
+            .execute_contract(
+                Addr::unchecked(AGENT),
+                cc_contracts.manager.clone(),
+                &ManagerExecuteMsg::ProxyCall { task_hash: None },
+                &[],
+            )
+            .unwrap();
+        limit_reached.push(
+            res.events
+                .iter()
+                .any(|e| e.ty == "wasm-vectis.cronkitty.v1.FailureLimitReached"),
+        );
+    }
+    assert_eq!(limit_reached, vec![false, true]);
+
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+    assert_eq!(action.status, ActionStatus::Paused);
+    assert_eq!(action.consecutive_failures, 0);
+}
+
+#[test]
+fn action_is_removed_after_consecutive_failures() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    register_cronkitty(&mut suite, REGISTRY_FEE);
+    let (proxy, cronkitty) = set_up_proxy_and_install_cronkitty(
+        &mut suite,
+        INSTALL_FEE,
+        1,
+        &cc_contracts.factory_addr,
+        1_000_000,
+    );
+
+    // the proxy does not have enough to send
+    let gas_limit = 150_000u64;
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: suite.deployer.to_string(),
+        amount: vec![coin(1_000_000_000, DENOM)],
+    });
+    let required = required_deposit(&suite, &cronkitty, gas_limit, msg.clone());
+    let required = coin(required.amount.u128() * 3, DENOM);
+    create_task(
+        &mut suite,
+        &proxy,
+        &cronkitty,
+        gas_limit,
+        required,
+        msg,
+        &cc_contracts.tasks_addr,
+    );
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::SetFailureLimit {
+                    action_id: 0,
+                    limit: Some(CronKittyFailureLimit {
+                        max_consecutive_failures: 1,
+                        on_limit: CronKittyOnFailureLimit::Remove,
+                    }),
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+    let task_hash = action.task_hash.unwrap();
+
+    // the agent call succeeds and the action is marked as failed, pending removal
+    suite.fast_forward_block_time(10000);
+    let res = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(AGENT),
+            cc_contracts.manager.clone(),
+            &ManagerExecuteMsg::ProxyCall { task_hash: None },
+            &[],
+        )
+        .unwrap();
+    assert!(res.events.iter().any(|e| {
+        e.ty == "wasm-vectis.cronkitty.v1.FailureLimitReached"
+            && e.attributes
+                .iter()
+                .any(|a| a.key == "Action" && a.value == "pending_removal")
+    }));
+
+    let action: CronKittyActionResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+        .unwrap();
+    assert_eq!(action.status, ActionStatus::Failed);
+
+    // later executions are rejected and not recorded
+    suite.fast_forward_block_time(10000);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(AGENT),
+            cc_contracts.manager.clone(),
+            &ManagerExecuteMsg::ProxyCall { task_hash: None },
+            &[],
+        )
+        .unwrap();
+    let history: CronKittyExecutionHistoryResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &cronkitty,
+            &CronKittyQueryMsg::ExecutionHistory {
+                action_id: 0,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(history.executions, 1);
+
+    // the task is removed by syncing the actions
+    let proxy_balance_before = suite.app.wrap().query_balance(&proxy, DENOM).unwrap();
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::SyncActions {
+                    start_after: None,
+                    limit: None,
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    let on_croncat: TaskResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&cc_contracts.tasks_addr, &TasksQueryMsg::Task { task_hash })
+        .unwrap();
+    assert!(on_croncat.task.is_none());
+
+    suite
+        .app
+        .wrap()
+        .query_wasm_smart::<CronKittyActionResp>(
+            &cronkitty,
+            &CronKittyQueryMsg::Action { action_id: 0 },
+        )
+        .unwrap_err();
+
+    // croncat refund to cronkitty is forwarded to the proxy
+    let proxy_balance_after = suite.app.wrap().query_balance(&proxy, DENOM).unwrap();
+    assert!(proxy_balance_after.amount > proxy_balance_before.amount);
+    let cronkitty_balance = suite.app.wrap().query_balance(&cronkitty, DENOM).unwrap();
+    assert!(cronkitty_balance.amount.is_zero());
+}
+
+#[test]
+fn spend_limit_is_enforced() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    register_cronkitty(&mut suite, REGISTRY_FEE);
+    let (proxy, cronkitty) = set_up_proxy_and_install_cronkitty(
+        &mut suite,
+        INSTALL_FEE,
+        1,
+        &cc_contracts.factory_addr,
+        1_000_000,
+    );
+
+    let gas_limit = 150_000u64;
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: suite.deployer.to_string(),
+        amount: vec![coin(10, DENOM)],
+    });
+    let required = required_deposit(&suite, &cronkitty, gas_limit, msg.clone());
+    let required = coin(required.amount.u128() * 3, DENOM);
+    create_task(
+        &mut suite,
+        &proxy,
+        &cronkitty,
+        gas_limit,
+        required,
+        msg,
+        &cc_contracts.tasks_addr,
+    );
+
+    let set_spend_limit = |suite: &mut HubChainSuite, limit: CronKittySpendLimit| {
+        suite.app.execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::SetSpendLimit {
+                    action_id: 0,
+                    limit: Some(limit),
+                },
+                vec![],
+            ),
+            &[],
+        )
+    };
+    // The agent is paid even if cronkitty rejects the execution,
+    // which is then not recorded
+    let agent_call = |suite: &mut HubChainSuite| {
+        suite.fast_forward_block_time(10000);
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(AGENT),
+                cc_contracts.manager.clone(),
+                &ManagerExecuteMsg::ProxyCall { task_hash: None },
+                &[],
+            )
+            .unwrap();
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart::<CronKittyExecutionHistoryResp>(
+                &cronkitty,
+                &CronKittyQueryMsg::ExecutionHistory {
+                    action_id: 0,
+                    limit: None,
+                },
+            )
+            .unwrap()
+            .executions
+    };
+    let spent = |suite: &HubChainSuite| {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart::<CronKittyActionResp>(
+                &cronkitty,
+                &CronKittyQueryMsg::Action { action_id: 0 },
+            )
+            .unwrap()
+            .spent
+    };
+
+    // more than allowed in one execution
+    set_spend_limit(
+        &mut suite,
+        CronKittySpendLimit {
+            per_execution: Some(vec![coin(5, DENOM)]),
+            total: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(agent_call(&mut suite), 0);
+    assert!(spent(&suite).is_empty());
+
+    set_spend_limit(
+        &mut suite,
+        CronKittySpendLimit {
+            per_execution: Some(vec![coin(10, DENOM)]),
+            total: Some(vec![coin(15, DENOM)]),
+        },
+    )
+    .unwrap();
+    assert_eq!(agent_call(&mut suite), 1);
+    assert_eq!(spent(&suite), vec![coin(10, DENOM)]);
+
+    // the second execution would exceed the total
+    assert_eq!(agent_call(&mut suite), 1);
+    assert_eq!(spent(&suite), vec![coin(10, DENOM)]);
+
+    // what custom msgs send cannot be capped
+    let update_to_custom_msg = |suite: &mut HubChainSuite| {
+        suite.app.execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::UpdateTask {
+                    action_id: 0,
+                    msgs: Some(vec![CosmosMsg::Custom(Empty {})]),
+                    interval: None,
+                    boundary: None,
+                },
+                vec![],
+            ),
+            &[],
+        )
+    };
+    let err = update_to_custom_msg(&mut suite).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::UnmeteredSpend.to_string()
+    );
+
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::SetSpendLimit {
+                    action_id: 0,
+                    limit: None,
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+    update_to_custom_msg(&mut suite).unwrap();
+    let err = set_spend_limit(
+        &mut suite,
+        CronKittySpendLimit {
+            per_execution: None,
+            total: Some(vec![coin(15, DENOM)]),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::UnmeteredSpend.to_string()
+    );
+
+    // nor can a budget be set
+    let err = suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::SetBudget {
+                    budget: Some(CronKittyBudget {
+                        limit: vec![coin(15, DENOM)],
+                        window: 15_000,
+                    }),
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::UnmeteredActions {
+            action_ids: vec![0]
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn budget_is_enforced_over_a_rolling_window() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let cc_contracts =
+        setup_croncat_contracts(&mut suite.app, &suite.deployer_signer, &suite.controller);
+    register_cronkitty(&mut suite, REGISTRY_FEE);
+    let (proxy, cronkitty) = set_up_proxy_and_install_cronkitty(
+        &mut suite,
+        INSTALL_FEE,
+        1,
+        &cc_contracts.factory_addr,
+        1_000_000,
+    );
+
+    let gas_limit = 150_000u64;
+    let msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: suite.deployer.to_string(),
+        amount: vec![coin(10, DENOM)],
+    });
+    let required = required_deposit(&suite, &cronkitty, gas_limit, msg.clone());
+    let required = coin(required.amount.u128() * 6, DENOM);
+    create_task(
+        &mut suite,
+        &proxy,
+        &cronkitty,
+        gas_limit,
+        required,
+        msg,
+        &cc_contracts.tasks_addr,
+    );
+
+    let err = suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::SetBudget {
+                    budget: Some(CronKittyBudget {
+                        limit: vec![coin(15, DENOM)],
+                        window: 0,
+                    }),
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::InvalidBudget.to_string()
+    );
+
+    let budget = CronKittyBudget {
+        limit: vec![coin(20, DENOM)],
+        window: 25_000,
+    };
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::SetBudget {
+                    budget: Some(budget.clone()),
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+
+    // The agent is paid even if cronkitty rejects the execution,
+    // which is then not recorded
+    let agent_call = |suite: &mut HubChainSuite| {
+        suite.fast_forward_block_time(10000);
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(AGENT),
+                cc_contracts.manager.clone(),
+                &ManagerExecuteMsg::ProxyCall { task_hash: None },
+                &[],
+            )
+            .unwrap();
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart::<CronKittyExecutionHistoryResp>(
+                &cronkitty,
+                &CronKittyQueryMsg::ExecutionHistory {
+                    action_id: 0,
+                    limit: None,
+                },
+            )
+            .unwrap()
+            .executions
+    };
+    let query_budget = |suite: &HubChainSuite| -> CronKittyBudgetResp {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Budget {})
+            .unwrap()
+    };
+
+    let resp = query_budget(&suite);
+    assert_eq!(resp.budget, Some(budget));
+    assert!(resp.spent.is_empty());
+    assert_eq!(resp.remaining, vec![coin(20, DENOM)]);
+
+    // t = 10_000
+    assert_eq!(agent_call(&mut suite), 1);
+    let resp = query_budget(&suite);
+    assert_eq!(resp.spent, vec![coin(10, DENOM)]);
+    assert_eq!(resp.remaining, vec![coin(10, DENOM)]);
+
+    // t = 30_000, both spends are within the window
+    suite.fast_forward_block_time(10000);
+    assert_eq!(agent_call(&mut suite), 2);
+    let resp = query_budget(&suite);
+    assert_eq!(resp.spent, vec![coin(20, DENOM)]);
+    assert!(resp.remaining[0].amount.is_zero());
+
+    // t = 40_000, the spend at t = 10_000 has left the window
+    assert_eq!(agent_call(&mut suite), 3);
+    let resp = query_budget(&suite);
+    assert_eq!(resp.spent, vec![coin(20, DENOM)]);
+
+    // t = 50_000, a fixed window restarted at t = 40_000 would allow it,
+    // but the spends at t = 30_000 and t = 40_000 are within the window
+    assert_eq!(agent_call(&mut suite), 3);
+    let resp = query_budget(&suite);
+    assert_eq!(resp.spent, vec![coin(20, DENOM)]);
+
+    // t = 60_000, the spend at t = 30_000 has left the window
+    assert_eq!(agent_call(&mut suite), 4);
+    let resp = query_budget(&suite);
+    assert_eq!(resp.spent, vec![coin(20, DENOM)]);
+}
+
+#[test]
+fn cw20_task_funding_and_refunds_work() {
+    let mut suite = HubChainSuite::init().unwrap();
+    let controller = suite.controller.clone();
+    let token = init_cw20(&mut suite.app, &controller, 1_000);
+    let cc_contracts = setup_croncat_contracts_with_cw20(
+        &mut suite.app,
+        &suite.deployer_signer,
+        &suite.controller,
+        vec![token.to_string()],
+    );
+    register_cronkitty(&mut suite, REGISTRY_FEE);
+    let (proxy, cronkitty) = set_up_proxy_and_install_cronkitty(
+        &mut suite,
+        INSTALL_FEE,
+        1,
+        &cc_contracts.factory_addr,
+        1_000_000,
+    );
+    suite
+        .app
+        .execute_contract(
+            controller,
+            token.clone(),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: proxy.to_string(),
+                amount: Uint128::from(1_000u128),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let send_cw20 = |suite: &mut HubChainSuite, amount: u128, msg: CronKittyReceiveMsg| {
+        suite
+            .app
+            .execute_contract(
+                suite.controller.clone(),
+                proxy.clone(),
+                &proxy_exec(
+                    &token,
+                    &Cw20ExecuteMsg::Send {
+                        contract: cronkitty.to_string(),
+                        amount: Uint128::from(amount),
+                        msg: to_binary(&msg).unwrap(),
+                    },
+                    vec![],
+                ),
+                &[],
+            )
+            .unwrap();
+    };
+    let cw20_balance = |suite: &HubChainSuite, addr: &Addr| {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart::<Cw20BalanceResponse>(
+                &token,
+                &Cw20QueryMsg::Balance {
+                    address: addr.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+    };
+    let task_cw20_balance = |suite: &HubChainSuite| {
+        let action: CronKittyActionResp = suite
+            .app
+            .wrap()
+            .query_wasm_smart(&cronkitty, &CronKittyQueryMsg::Action { action_id: 0 })
+            .unwrap();
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart::<TaskBalanceResponse>(
+                &cc_contracts.manager,
+                &ManagerQueryMsg::TaskBalance {
+                    task_hash: action.task_hash.unwrap(),
+                },
+            )
+            .unwrap()
+            .balance
+            .unwrap()
+            .cw20_balance
+    };
+
+    let gas_limit = 150_000u64;
+    let msg = CosmosMsg::Bank(BankMsg::Burn {
+        amount: vec![coin(1, DENOM)],
+    });
+    let required = required_deposit(&suite, &cronkitty, gas_limit, msg.clone());
+    let mut task = task_request(gas_limit, msg);
+    task.cw20 = Some(Cw20Coin {
+        address: token.to_string(),
+        amount: Uint128::from(100u128),
+    });
+    let create_task_msg = proxy_exec(
+        &cronkitty,
+        &CronKittyExecMsg::CreateTask { task, label: None },
+        vec![required],
+    );
+
+    // cw20 must be deposited first
+    let err = suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &create_task_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::InsufficientCw20Deposit {
+            token: token.to_string()
+        }
+        .to_string()
+    );
+
+    send_cw20(&mut suite, 100, CronKittyReceiveMsg::Deposit {});
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &create_task_msg,
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        task_cw20_balance(&suite),
+        Some(Cw20CoinVerified {
+            address: token.clone(),
+            amount: Uint128::from(100u128),
+        })
+    );
+
+    send_cw20(
+        &mut suite,
+        50,
+        CronKittyReceiveMsg::RefillTaskCw20 { action_id: 0 },
+    );
+    assert_eq!(
+        task_cw20_balance(&suite),
+        Some(Cw20CoinVerified {
+            address: token.clone(),
+            amount: Uint128::from(150u128),
+        })
+    );
+
+    // unused deposits are returned to the proxy
+    send_cw20(&mut suite, 30, CronKittyReceiveMsg::Deposit {});
+    assert_eq!(cw20_balance(&suite, &proxy), Uint128::from(820u128));
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(&cronkitty, &CronKittyExecMsg::SweepCw20Funds {}, vec![]),
+            &[],
+        )
+        .unwrap();
+    assert_eq!(cw20_balance(&suite, &proxy), Uint128::from(850u128));
+
+    // the cw20 balance of the task is returned to the proxy when it is removed
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::RemoveTask { task_id: 0 },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+    assert_eq!(cw20_balance(&suite, &proxy), Uint128::from(1_000u128));
+    assert!(cw20_balance(&suite, &cronkitty).is_zero());
+    assert!(cw20_balance(&suite, &cc_contracts.manager).is_zero());
+
+    // no task can be created to use deposits after teardown
+    suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &cronkitty,
+                &CronKittyExecMsg::Teardown { limit: None },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap();
+    let err = suite
+        .app
+        .execute_contract(
+            suite.controller.clone(),
+            proxy.clone(),
+            &proxy_exec(
+                &token,
+                &Cw20ExecuteMsg::Send {
+                    contract: cronkitty.to_string(),
+                    amount: Uint128::from(30u128),
+                    msg: to_binary(&CronKittyReceiveMsg::Deposit {}).unwrap(),
+                },
+                vec![],
+            ),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::Terminated.to_string()
+    );
+}